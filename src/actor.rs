@@ -1,31 +1,44 @@
 pub(crate) mod actor_cell;
 pub(crate) mod actor_ref;
+pub(crate) mod ask;
+pub(crate) mod async_actor;
 pub(crate) mod channel;
 pub(crate) mod macros;
 pub(crate) mod props;
+pub(crate) mod router;
+pub(crate) mod selection;
+pub(crate) mod supervision;
 pub(crate) mod uri;
 
 use std::{error, fmt};
 
-use crate::validate::InvalidName;
+pub use crate::validate::{InvalidName, InvalidPath};
 
 // Public API (plus the pub data types in this file)
 pub use self::{
     actor_cell::Context,
     actor_ref::{
-        ActorRef, ActorRefFactory, ActorReference, BasicActorRef, BoxedTell, Sender, Tell,
+        ActorRef, ActorRefFactory, ActorReference, AnonActorRefFactory, BasicActorRef, BoxedTell,
+        Sender, Tell,
     },
+    async_actor::{Async, AsyncActor, BoxFuture},
     channel::{
-        channel, All, Channel, ChannelMsg, ChannelRef, DLChannelMsg, DeadLetter, EventsChannel,
-        Publish, Subscribe, SubscribeWithResponse, SubscribedResponse, SysTopic, Topic,
-        Unsubscribe, UnsubscribeAll,
+        channel, All, Channel, ChannelMsg, ChannelRef, DLChannelMsg, DeadLetter, DeadLetterReason,
+        EventsChannel, Publish, Subscribe, SubscribeWithResponse, SubscribedResponse, SysTopic,
+        Topic, Unsubscribe, UnsubscribeAll,
     },
     macros::actor,
     props::{ActorArgs, ActorFactory, ActorFactoryArgs, ActorProducer, BoxActorProd, Props},
+    router::{
+        Broadcast, ConsistentHashable, Resizer, Router, RouterConfig, RouterMsg, RouterRef,
+        RoutingLogic,
+    },
+    selection::{ActorSelection, ActorSelectionFactory},
+    supervision::{Backoff, RestartLimit, Strategy, SupervisorScope, SupervisorStrategy},
     uri::{ActorPath, ActorUri},
 };
 
-use crate::{system::SystemMsg, Message};
+use crate::{system::SystemMsg, Envelope, Message};
 
 pub type MsgResult<T> = Result<(), MsgError<T>>;
 
@@ -34,17 +47,24 @@ pub type MsgResult<T> = Result<(), MsgError<T>>;
 #[derive(Clone)]
 pub struct MsgError<T> {
     pub msg: T,
+    pub reason: DeadLetterReason,
 }
 
 impl<T> MsgError<T> {
     pub fn new(msg: T) -> Self {
-        MsgError { msg }
+        MsgError {
+            msg,
+            reason: DeadLetterReason::Terminated,
+        }
     }
 }
 
 impl<T> fmt::Display for MsgError<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str("The actor does not exist. It may have been terminated")
+        match self.reason {
+            DeadLetterReason::MailboxFull => f.write_str("The actor's mailbox is full"),
+            _ => f.write_str("The actor does not exist. It may have been terminated"),
+        }
     }
 }
 
@@ -77,6 +97,76 @@ impl<T> fmt::Debug for TryMsgError<T> {
     }
 }
 
+/// Error type when `Context::stash` fails because the stash is full.
+pub struct StashError<T> {
+    pub msg: T,
+}
+
+impl<T> StashError<T> {
+    pub fn new(msg: T) -> Self {
+        StashError { msg }
+    }
+}
+
+impl<T> fmt::Display for StashError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Failed to stash message. Cause: The stash is full")
+    }
+}
+
+impl<T> fmt::Debug for StashError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string())
+    }
+}
+
+/// Error type when a message can't be added to an actor's mailbox.
+///
+/// Returned by `ActorRef::try_tell` and `ActorRef::send` with the message that was not sent.
+pub enum SendError<T> {
+    Full(T),
+    Terminated(T),
+}
+
+impl<T> SendError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(msg) | Self::Terminated(msg) => msg,
+        }
+    }
+}
+
+impl<T> error::Error for SendError<T> {}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Full(_) => f.write_str("Failed to send message. Cause: The mailbox is full"),
+            Self::Terminated(_) => {
+                f.write_str("Failed to send message. Cause: The actor has been terminated")
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string())
+    }
+}
+
+impl<T> From<MsgError<Envelope<T>>> for SendError<T>
+where
+    T: Message,
+{
+    fn from(err: MsgError<Envelope<T>>) -> SendError<T> {
+        match err.reason {
+            DeadLetterReason::MailboxFull => SendError::Full(err.msg.msg),
+            _ => SendError::Terminated(err.msg.msg),
+        }
+    }
+}
+
 /// Error type when an actor fails to start during `actor_of`.
 #[derive(Debug)]
 pub enum CreateError {
@@ -113,6 +203,39 @@ impl From<InvalidName> for CreateError {
     }
 }
 
+/// Error type when an `ask` does not receive a reply.
+#[derive(Debug)]
+pub enum AskError {
+    Timeout,
+    Terminated,
+    MailboxFull,
+    Create(CreateError),
+}
+
+impl error::Error for AskError {}
+
+impl fmt::Display for AskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Timeout => f.write_str("Ask failed. Cause: No reply before the timeout elapsed"),
+            Self::Terminated => {
+                f.write_str("Ask failed. Cause: The actor terminated before replying")
+            }
+            Self::MailboxFull => f.write_str("Ask failed. Cause: The actor's mailbox is full"),
+            Self::Create(ref err) => f.write_str(&format!(
+                "Ask failed. Cause: Failed to create the reply actor ({})",
+                err
+            )),
+        }
+    }
+}
+
+impl From<CreateError> for AskError {
+    fn from(err: CreateError) -> AskError {
+        AskError::Create(err)
+    }
+}
+
 /// Error type when an actor fails to restart.
 pub struct RestartError;
 
@@ -128,6 +251,10 @@ impl fmt::Debug for RestartError {
     }
 }
 
+/// A message handler that replaces `Actor::recv`, set with `Context::become`
+pub type Behavior<A> =
+    Box<dyn FnMut(&mut A, &Context<<A as Actor>::Msg>, <A as Actor>::Msg, Sender) + Send + 'static>;
+
 #[allow(unused_variables)]
 pub trait Actor: Send + 'static {
     type Msg: Message;
@@ -163,6 +290,21 @@ pub trait Actor: Send + 'static {
     /// It is guaranteed that only one message in the actor's mailbox is processed
     /// at any one time, including `recv` and `sys_recv`.
     fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, sender: Sender);
+
+    /// Return a supervisor strategy that will be used when handling failed child actors.
+    ///
+    /// Defaults to restarting only the failed child.
+    fn supervisor_strategy(&self) -> SupervisorStrategy {
+        SupervisorStrategy::default()
+    }
+
+    /// Return `true` to keep stashed messages when this actor is restarted,
+    /// so that the new instance can unstash them.
+    ///
+    /// Defaults to `false`, publishing them to dead letters.
+    fn keep_stash_on_restart(&self) -> bool {
+        false
+    }
 }
 
 impl<A: Actor + ?Sized> Actor for Box<A> {
@@ -192,6 +334,14 @@ impl<A: Actor + ?Sized> Actor for Box<A> {
     fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
         (**self).recv(ctx, msg, sender)
     }
+
+    fn supervisor_strategy(&self) -> SupervisorStrategy {
+        (**self).supervisor_strategy()
+    }
+
+    fn keep_stash_on_restart(&self) -> bool {
+        (**self).keep_stash_on_restart()
+    }
 }
 
 /// Receive and handle a specific message type
@@ -254,7 +404,7 @@ impl<A: Actor + ?Sized> Actor for Box<A> {
 ///
 ///     actor.tell(Foo, None);
 ///     actor.tell(Bar, None);
-///     sys.shutdown().await
+///     sys.shutdown().await;
 /// }
 /// ```
 pub trait Receive<Msg: Message> {
@@ -1,25 +1,34 @@
 use std::{
+    any::{Any, TypeId},
     collections::HashMap,
     fmt,
+    future::{poll_fn, Future},
     ops::Deref,
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
-        Arc, RwLock,
+        Arc, Mutex, RwLock,
     },
+    task::Poll,
     time::{Duration, Instant},
 };
 
+use tokio::sync::watch;
 use uuid::Uuid;
 
 use crate::{
-    actor::{props::ActorFactory, *},
+    actor::{
+        async_actor::PendingRecv, channel::is_dead_letter, props::ActorFactory,
+        supervision::RestartStats, *,
+    },
     kernel::{
-        kernel_ref::{dispatch, dispatch_any, KernelRef},
-        mailbox::{AnyEnqueueError, AnySender, MailboxSender},
+        kernel_ref::{dispatch, dispatch_any, dispatch_wait, KernelRef},
+        mailbox::{AnyEnqueueError, AnySender, MailboxSchedule, MailboxSender},
     },
     system::{
-        timer::{Job, OnceJob, RepeatJob, ScheduleId, Timer},
-        ActorSystem, SystemCmd, SystemMsg,
+        timer::{
+            ActiveTimer, CronJob, Job, OnceJob, RepeatSchedule, ScheduleHandle, ScheduleId, Timer,
+        },
+        ActorSystem, CronSchedule, SystemCmd, SystemMsg,
     },
     AnyMessage, Envelope, Message,
 };
@@ -32,16 +41,45 @@ pub struct ActorCell {
 #[derive(Clone)]
 struct ActorCellInner {
     uri: ActorUri,
+    /// Identifies the actor, unlike its path which a new actor can reuse
+    uid: Uuid,
     parent: Option<BasicActorRef>,
     children: Children,
+    escalated: Arc<Mutex<Vec<BasicActorRef>>>,
+    restarts: Arc<Mutex<HashMap<String, RestartStats>>>,
+    watchers: Arc<Mutex<Watchers>>,
+    /// The actors this actor watches, until they terminate or are unwatched
+    watching: Arc<Mutex<HashMap<ActorPath, BasicActorRef>>>,
     is_remote: bool,
     is_terminating: Arc<AtomicBool>,
     is_restarting: Arc<AtomicBool>,
     status: Arc<AtomicUsize>,
+    /// Set to `true` when the actor stops, cancelling the futures it pipes
+    stopped: Arc<watch::Sender<bool>>,
+    stopped_rx: watch::Receiver<bool>,
     kernel: Option<KernelRef>,
     system: ActorSystem,
     mailbox: Arc<dyn AnySender>,
     sys_mailbox: MailboxSender<SystemMsg>,
+    /// The `TypedCell` of the actor, if it has one
+    typed: Arc<dyn Any + Send + Sync>,
+    /// The timers started through the actor's `Context`
+    timers: Arc<Mutex<Timers>>,
+}
+
+#[derive(Default)]
+struct Timers {
+    active: Vec<ActiveTimer>,
+    /// The key of each timer that was not cancelled or replaced, with the number of
+    /// its messages in the mailbox. A timer that no longer fires is kept until they are received.
+    queued: HashMap<ScheduleId, (Option<String>, usize)>,
+}
+
+impl Timers {
+    /// Forgets the timers started with `key`, so that their messages in the mailbox are dropped.
+    fn forget_key(&mut self, key: &str) {
+        self.queued.retain(|_, (k, _)| k.as_deref() != Some(key));
+    }
 }
 
 impl ActorCell {
@@ -53,19 +91,29 @@ impl ActorCell {
         mailbox: Arc<dyn AnySender>,
         sys_mailbox: MailboxSender<SystemMsg>,
     ) -> ActorCell {
+        let (stopped, stopped_rx) = watch::channel(false);
         ActorCell {
             inner: Arc::new(ActorCellInner {
                 uri,
+                uid: Uuid::new_v4(),
                 parent,
                 children: Children::new(),
+                escalated: Arc::new(Mutex::new(Vec::new())),
+                restarts: Arc::new(Mutex::new(HashMap::new())),
+                watchers: Arc::new(Mutex::new(Watchers::default())),
+                watching: Arc::new(Mutex::new(HashMap::new())),
                 is_remote: false,
                 is_terminating: Arc::new(AtomicBool::new(false)),
                 is_restarting: Arc::new(AtomicBool::new(false)),
                 status: Arc::new(AtomicUsize::new(0)),
+                stopped: Arc::new(stopped),
+                stopped_rx,
                 kernel: None,
                 system: system.clone(),
                 mailbox,
                 sys_mailbox,
+                typed: Arc::new(()),
+                timers: Arc::new(Mutex::new(Timers::default())),
             }),
         }
     }
@@ -85,6 +133,15 @@ impl ActorCell {
         self.inner.kernel.as_ref().unwrap()
     }
 
+    /// The typed cell of the actor, if it receives messages of type `Msg`.
+    pub(crate) fn typed<Msg: Message>(&self) -> Option<ExtendedCell<Msg>> {
+        let typed = self.inner.typed.clone().downcast::<TypedCell<Msg>>().ok()?;
+        Some(ExtendedCell {
+            cell: self.clone(),
+            typed,
+        })
+    }
+
     pub(crate) fn myself(&self) -> BasicActorRef {
         BasicActorRef { cell: self.clone() }
     }
@@ -110,29 +167,102 @@ impl ActorCell {
     }
 
     pub(crate) fn is_root(&self) -> bool {
-        self.myself().path() == "/"
+        self.myself().path().is_root()
     }
 
     pub fn is_user(&self) -> bool {
         self.inner.system.user_root().is_child(&self.myself())
     }
 
+    /// Number of messages waiting in the actor's mailbox.
+    pub(crate) fn mailbox_len(&self) -> usize {
+        self.inner.mailbox.queue_len()
+    }
+
+    /// Whether the actor is processing, or scheduled to process, its mailbox.
+    pub(crate) fn is_busy(&self) -> bool {
+        self.inner.mailbox.is_sched()
+    }
+
     pub(crate) fn send_any_msg(
         &self,
         msg: &mut AnyMessage,
         sender: crate::actor::Sender,
+    ) -> Result<(), AnyEnqueueError> {
+        self.send_any(msg, sender, None)
+    }
+
+    /// Sends the message of the timer `id` started by this actor to itself,
+    /// unless the timer was cancelled meanwhile.
+    pub(crate) fn send_timer_msg(
+        &self,
+        id: &ScheduleId,
+        msg: &mut AnyMessage,
+        sender: crate::actor::Sender,
+    ) -> Result<(), AnyEnqueueError> {
+        match self.inner.timers.lock().unwrap().queued.get_mut(id) {
+            Some((_, queued)) => *queued += 1,
+            None => return Ok(()),
+        }
+
+        let result = self.send_any(msg, sender, Some(*id));
+        if result.is_err() {
+            self.dequeue_timer_msg(id);
+        }
+        result
+    }
+
+    fn send_any(
+        &self,
+        msg: &mut AnyMessage,
+        sender: crate::actor::Sender,
+        timer: Option<ScheduleId>,
     ) -> Result<(), AnyEnqueueError> {
         let mb = &self.inner.mailbox;
         let k = self.kernel();
 
-        dispatch_any(msg, sender, mb, k)
+        match dispatch_any(msg, sender, timer, mb, k) {
+            Ok(dropped) => {
+                if let Some(dropped) = dropped {
+                    if let Some(id) = &dropped.timer {
+                        self.dequeue_timer_msg(id);
+                    }
+                    self.dead_letter(dropped, DeadLetterReason::MailboxFull);
+                }
+                Ok(())
+            }
+            Err(mut e) => {
+                if let Some(rejected) = e.rejected.take() {
+                    self.dead_letter(rejected, DeadLetterReason::MailboxFull);
+                }
+                Err(e)
+            }
+        }
     }
 
     pub(crate) fn send_sys_msg(&self, msg: Envelope<SystemMsg>) -> MsgResult<Envelope<SystemMsg>> {
         let mb = &self.inner.sys_mailbox;
 
         let k = self.kernel();
-        dispatch(msg, mb, k)
+        dispatch(msg, mb, k).map(|_| ())
+    }
+
+    /// Publishes a message that could not be delivered to this actor to dead letters.
+    pub(crate) fn dead_letter(&self, msg: Envelope<String>, reason: DeadLetterReason) {
+        let dl = DeadLetter {
+            msg: msg.msg,
+            sender: msg.sender,
+            recipient: self.myself(),
+            reason,
+        };
+
+        self.inner.system.dead_letters().tell(
+            Publish {
+                topic: "dead_letter".into(),
+                msg: dl,
+            },
+            None,
+        );
     }
 
     pub(crate) fn is_child(&self, actor: &BasicActorRef) -> bool {
@@ -156,6 +286,7 @@ impl ActorCell {
         match cmd {
             SystemCmd::Stop => self.terminate(actor),
             SystemCmd::Restart => self.restart(),
+            SystemCmd::Resume => self.resume(),
         }
     }
 
@@ -165,6 +296,7 @@ impl ActorCell {
         // *3. Wait for ActorTerminated from each child
 
         self.inner.is_terminating.store(true, Ordering::Relaxed);
+        self.cancel_timers();
 
         if !self.has_children() {
             self.kernel().terminate();
@@ -175,6 +307,9 @@ impl ActorCell {
     }
 
     pub fn restart(&self) {
+        self.inner.escalated.lock().unwrap().clear();
+        self.cancel_timers();
+
         if !self.has_children() {
             self.kernel().restart();
         } else {
@@ -183,7 +318,231 @@ impl ActorCell {
         }
     }
 
+    /// Resumes the children whose failure was escalated by this actor.
+    ///
+    /// Resuming this actor's own mailbox is done by the mailbox itself.
+    pub fn resume(&self) {
+        for child in self.inner.escalated.lock().unwrap().drain(..) {
+            child.sys_tell(SystemCmd::Resume.into());
+        }
+    }
+
+    /// Registers `watcher` to be notified when this actor terminates.
+    ///
+    /// If this actor has already terminated the notification is sent immediately.
+    pub(crate) fn add_watcher(&self, watcher: BasicActorRef, msg: Option<AnyMessage>) {
+        let mut watchers = self.inner.watchers.lock().unwrap();
+        if watchers.terminated {
+            drop(watchers);
+            notify_watcher(&self.myself(), watcher, msg);
+        } else {
+            watchers.entries.retain(|(w, _)| *w != watcher);
+            watchers.entries.push((watcher, msg));
+        }
+    }
+
+    pub(crate) fn remove_watcher(&self, watcher: &BasicActorRef) {
+        self.inner
+            .watchers
+            .lock()
+            .unwrap()
+            .entries
+            .retain(|(w, _)| w != watcher);
+    }
+
+    /// Watches `actor` for termination on behalf of this actor.
+    pub(crate) fn watch(&self, actor: BasicActorRef, msg: Option<AnyMessage>) {
+        // registered first, as an actor that already terminated is forgotten right away
+        self.inner
+            .watching
+            .lock()
+            .unwrap()
+            .insert(actor.path().clone(), actor.clone());
+
+        actor.cell.add_watcher(self.myself(), msg);
+    }
+
+    pub(crate) fn unwatch(&self, actor: &BasicActorRef) {
+        actor.cell.remove_watcher(&self.myself());
+        self.inner.watching.lock().unwrap().remove(actor.path());
+    }
+
+    /// Forgets the watched `actor` once it terminated,
+    /// unless a new actor at the same path is watched.
+    fn forget_watched(&self, actor: &BasicActorRef) {
+        let mut watching = self.inner.watching.lock().unwrap();
+        if let Some(watched) = watching.get(actor.path()) {
+            if watched.cell.is_same(&actor.cell) {
+                watching.remove(actor.path());
+            }
+        }
+    }
+
+    /// The actors watched by this actor.
+    pub(crate) fn watching(&self) -> Vec<BasicActorRef> {
+        self.inner
+            .watching
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Whether `other` is a cell of the same actor,
+    /// rather than of a different actor created at the same path.
+    pub(crate) fn is_same(&self, other: &ActorCell) -> bool {
+        self.inner.uid == other.inner.uid
+    }
+
+    /// Notifies all watchers of this actor's termination, exactly once,
+    /// and stops watching other actors.
+    pub(crate) fn notify_watchers(&self) {
+        let entries = {
+            let mut watchers = self.inner.watchers.lock().unwrap();
+            watchers.terminated = true;
+            std::mem::take(&mut watchers.entries)
+        };
+
+        let myself = self.myself();
+        for (watcher, msg) in entries {
+            notify_watcher(&myself, watcher, msg);
+        }
+
+        for (_, actor) in self.inner.watching.lock().unwrap().drain() {
+            actor.cell.remove_watcher(&myself);
+        }
+    }
+
+    /// Runs `future` on the system's runtime until it completes or this actor stops,
+    /// passing its output to `deliver` if it completed.
+    pub(crate) fn spawn_until_stopped<F, D>(&self, future: F, deliver: D)
+    where
+        F: Future + Send + 'static,
+        D: FnOnce(F::Output) + Send + 'static,
+    {
+        let mut stopped = self.inner.stopped_rx.clone();
+        self.inner.system.backend.spawn(async move {
+            let mut future = Box::pin(future);
+            let mut stop = Box::pin(stopped.changed());
+            let output = poll_fn(|cx| match future.as_mut().poll(cx) {
+                Poll::Ready(output) => Poll::Ready(Some(output)),
+                Poll::Pending => stop.as_mut().poll(cx).map(|_| None),
+            })
+            .await;
+
+            if let Some(output) = output {
+                deliver(output);
+            }
+        });
+    }
+
+    /// Cancels the futures piped by this actor.
+    pub(crate) fn cancel_pipes(&self) {
+        let _ = self.inner.stopped.send(true);
+    }
+
+    /// Whether this actor has stopped.
+    pub(crate) fn is_stopped(&self) -> bool {
+        *self.inner.stopped_rx.borrow()
+    }
+
+    /// Registers a timer started by this actor, returning the id of the active
+    /// timer with the same key, which the new timer replaces.
+    ///
+    /// Messages of the replaced timer that are still in the mailbox are dropped.
+    pub(crate) fn add_timer(&self, timer: ActiveTimer) -> Option<ScheduleId> {
+        let mut timers = self.inner.timers.lock().unwrap();
+        let replaced = match &timer.key {
+            Some(key) => {
+                timers.forget_key(key);
+                timers
+                    .active
+                    .iter()
+                    .position(|t| t.key.as_ref() == Some(key))
+                    .map(|i| timers.active.remove(i).id)
+            }
+            None => None,
+        };
+        timers.queued.insert(timer.id, (timer.key.clone(), 0));
+        timers.active.push(timer);
+        replaced
+    }
+
+    /// Removes a timer that no longer fires, keeping its messages in the mailbox.
+    pub(crate) fn remove_timer(&self, id: &ScheduleId) {
+        let mut timers = self.inner.timers.lock().unwrap();
+        timers.active.retain(|t| &t.id != id);
+        if matches!(timers.queued.get(id), Some((_, 0))) {
+            timers.queued.remove(id);
+        }
+    }
+
+    /// Removes the timer started with `key`, dropping its messages in the mailbox.
+    pub(crate) fn remove_keyed_timer(&self, key: &str) -> Option<ScheduleId> {
+        let mut timers = self.inner.timers.lock().unwrap();
+        timers.forget_key(key);
+        let i = timers
+            .active
+            .iter()
+            .position(|t| t.key.as_deref() == Some(key))?;
+        Some(timers.active.remove(i).id)
+    }
+
+    pub(crate) fn timers(&self) -> Vec<ActiveTimer> {
+        self.inner.timers.lock().unwrap().active.clone()
+    }
+
+    /// Accounts for a message of the timer `id` leaving the mailbox,
+    /// returning `false` if the timer was cancelled or replaced since it was sent.
+    pub(crate) fn dequeue_timer_msg(&self, id: &ScheduleId) -> bool {
+        let mut timers = self.inner.timers.lock().unwrap();
+        let queued = match timers.queued.get_mut(id) {
+            Some((_, queued)) => queued,
+            None => return false,
+        };
+
+        *queued = queued.saturating_sub(1);
+        if *queued == 0 && !timers.active.iter().any(|t| &t.id == id) {
+            timers.queued.remove(id);
+        }
+        true
+    }
+
+    /// Cancels the timers started by this actor, dropping their messages in the mailbox.
+    fn cancel_timers(&self) {
+        let timers: Vec<ActiveTimer> = {
+            let mut timers = self.inner.timers.lock().unwrap();
+            timers.queued.clear();
+            timers.active.drain(..).collect()
+        };
+        if !timers.is_empty() {
+            let timer = self.inner.system.timer.lock().unwrap();
+            for t in timers {
+                let _ = timer.send(Job::Cancel(t.id));
+            }
+        }
+    }
+
+    /// Completes once this actor has stopped.
+    pub(crate) fn stopped(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut stopped = self.inner.stopped_rx.clone();
+        async move {
+            while !*stopped.borrow() {
+                if stopped.changed().await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
     pub fn death_watch<A: Actor>(&self, terminated: &BasicActorRef, actor: &mut Option<A>) {
+        self.inner
+            .restarts
+            .lock()
+            .unwrap()
+            .remove(terminated.name());
+
         if self.remove_child_is_empty(terminated) {
             // No children exist. Stop this actor's kernel.
             if self.inner.is_terminating.load(Ordering::Relaxed) {
@@ -199,14 +558,83 @@ impl ActorCell {
         }
     }
 
-    pub fn handle_failure(&self, failed: BasicActorRef) {
-        self.restart_child(&failed)
+    pub fn handle_failure(&self, failed: BasicActorRef, strategy: SupervisorStrategy) {
+        match strategy.strategy {
+            Strategy::Restart => {
+                let delay = self
+                    .inner
+                    .restarts
+                    .lock()
+                    .unwrap()
+                    .entry(failed.name().to_string())
+                    .or_default()
+                    .restart(&strategy, Instant::now());
+
+                match (delay, strategy.scope) {
+                    (Some(delay), SupervisorScope::OneForOne) => {
+                        self.restart_child_after(&failed, delay)
+                    }
+                    (Some(delay), SupervisorScope::AllForOne) => self
+                        .inner
+                        .children
+                        .for_each(|child| self.restart_child_after(child, delay)),
+                    (None, _) => {
+                        let mut exceeded = strategy.restart_limit.as_ref().unwrap().exceeded;
+                        if exceeded == Strategy::Restart {
+                            exceeded = Strategy::Stop;
+                        }
+
+                        slog::warn!(
+                            self.inner.system.log(),
+                            "Actor {} exceeded its restart limit, applying {:?}",
+                            failed.path(),
+                            exceeded
+                        );
+                        self.handle_failure(
+                            failed,
+                            SupervisorStrategy {
+                                strategy: exceeded,
+                                ..strategy
+                            },
+                        )
+                    }
+                }
+            }
+            Strategy::Stop => match strategy.scope {
+                SupervisorScope::OneForOne => self.stop(&failed),
+                SupervisorScope::AllForOne => {
+                    self.inner.children.for_each(|child| self.stop(child))
+                }
+            },
+            // siblings of the failed child were never suspended
+            Strategy::Resume => self.resume_child(&failed),
+            Strategy::Escalate => {
+                self.inner.escalated.lock().unwrap().push(failed);
+                self.escalate_failure();
+            }
+        }
     }
 
     pub fn restart_child(&self, actor: &BasicActorRef) {
         actor.sys_tell(SystemCmd::Restart.into());
     }
 
+    fn restart_child_after(&self, actor: &BasicActorRef, delay: Duration) {
+        if delay == Duration::default() {
+            self.restart_child(actor);
+        } else {
+            let actor = actor.clone();
+            self.inner.system.backend.spawn(async move {
+                tokio::time::sleep(delay).await;
+                actor.sys_tell(SystemCmd::Restart.into());
+            });
+        }
+    }
+
+    pub fn resume_child(&self, actor: &BasicActorRef) {
+        actor.sys_tell(SystemCmd::Resume.into());
+    }
+
     pub fn escalate_failure(&self) {
         self.inner
             .parent
@@ -216,6 +644,24 @@ impl ActorCell {
     }
 }
 
+/// Actors watching an actor for termination
+#[derive(Default)]
+struct Watchers {
+    terminated: bool,
+    entries: Vec<(BasicActorRef, Option<AnyMessage>)>,
+}
+
+fn notify_watcher(terminated: &BasicActorRef, watcher: BasicActorRef, msg: Option<AnyMessage>) {
+    watcher.cell.forget_watched(terminated);
+
+    match msg {
+        Some(mut msg) => {
+            let _ = watcher.try_tell_any(&mut msg, terminated.clone());
+        }
+        None => watcher.sys_tell(SystemMsg::Terminated(terminated.clone())),
+    }
+}
+
 impl<Msg: Message> From<ExtendedCell<Msg>> for ActorCell {
     fn from(cell: ExtendedCell<Msg>) -> Self {
         cell.cell
@@ -231,7 +677,41 @@ impl fmt::Debug for ActorCell {
 #[derive(Clone)]
 pub struct ExtendedCell<Msg: Message> {
     cell: ActorCell,
+    typed: Arc<TypedCell<Msg>>,
+}
+
+/// The state of a cell that depends on the actor's message type,
+/// also kept type-erased by its `ActorCell` to look up typed references.
+struct TypedCell<Msg: Message> {
     mailbox: MailboxSender<Msg>,
+    stash: Mutex<Vec<Envelope<Msg>>>,
+    behaviors: Behaviors,
+    actor_type: TypeId,
+    pending: Mutex<Option<PendingRecv>>,
+}
+
+/// Stack of `Behavior<A>` of the actor, type-erased since the cell does not know `A`.
+/// A behavior is `None` while it is running.
+type Behaviors = Mutex<Vec<Option<Box<dyn Any + Send>>>>;
+
+/// A behavior taken out of its slot while it runs.
+/// It is put back when dropped, also if it panics, unless it was removed with `unbecome`.
+struct RunningBehavior<'a> {
+    behaviors: &'a Behaviors,
+    i: usize,
+    behavior: Option<Box<dyn Any + Send>>,
+}
+
+impl<'a> Drop for RunningBehavior<'a> {
+    fn drop(&mut self) {
+        let mut behaviors = self
+            .behaviors
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(slot @ None) = behaviors.get_mut(self.i) {
+            *slot = self.behavior.take();
+        }
+    }
 }
 
 impl<Msg> ExtendedCell<Msg>
@@ -245,24 +725,43 @@ where
         any_mailbox: Arc<dyn AnySender>,
         sys_mailbox: MailboxSender<SystemMsg>,
         mailbox: MailboxSender<Msg>,
+        actor_type: TypeId,
     ) -> Self {
+        let typed = Arc::new(TypedCell {
+            mailbox,
+            stash: Mutex::new(Vec::new()),
+            behaviors: Mutex::new(Vec::new()),
+            actor_type,
+            pending: Mutex::new(None),
+        });
+
+        let (stopped, stopped_rx) = watch::channel(false);
         let cell = ActorCell {
             inner: Arc::new(ActorCellInner {
                 uri,
+                uid: Uuid::new_v4(),
                 parent,
                 children: Children::new(),
+                escalated: Arc::new(Mutex::new(Vec::new())),
+                restarts: Arc::new(Mutex::new(HashMap::new())),
+                watchers: Arc::new(Mutex::new(Watchers::default())),
+                watching: Arc::new(Mutex::new(HashMap::new())),
                 is_remote: false,
                 is_terminating: Arc::new(AtomicBool::new(false)),
                 is_restarting: Arc::new(AtomicBool::new(false)),
                 status: Arc::new(AtomicUsize::new(0)),
+                stopped: Arc::new(stopped),
+                stopped_rx,
                 kernel: None,
                 system: system.clone(),
                 mailbox: any_mailbox,
                 sys_mailbox,
+                typed: typed.clone(),
+                timers: Arc::new(Mutex::new(Timers::default())),
             }),
         };
 
-        ExtendedCell { cell, mailbox }
+        ExtendedCell { cell, typed }
     }
 
     pub(crate) fn init(self, kernel: &KernelRef) -> Self {
@@ -308,29 +807,158 @@ where
     }
 
     pub(crate) fn send_msg(&self, msg: Envelope<Msg>) -> MsgResult<Envelope<Msg>> {
-        let mb = &self.mailbox;
+        let mb = &self.typed.mailbox;
         let k = self.cell.kernel();
 
-        dispatch(msg, mb, k).map_err(|e| {
-            let dl = e.clone(); // clone the failed message and send to dead letters
-            let dl = DeadLetter {
-                msg: format!("{:?}", dl.msg.msg),
-                sender: dl.msg.sender,
-                recipient: self.cell.myself(),
-            };
-
-            self.cell.inner.system.dead_letters().tell(
-                Publish {
-                    topic: "dead_letter".into(),
-                    msg: dl,
-                },
-                None,
-            );
+        match dispatch(msg, mb, k) {
+            Ok(dropped) => {
+                if let Some(dropped) = dropped {
+                    if let Some(id) = &dropped.timer {
+                        self.cell.dequeue_timer_msg(id);
+                    }
+                    self.dead_letter(&dropped, DeadLetterReason::MailboxFull);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.dead_letter(&e.msg, e.reason);
+                Err(e)
+            }
+        }
+    }
 
+    pub(crate) fn dequeue_timer_msg(&self, id: &ScheduleId) -> bool {
+        self.cell.dequeue_timer_msg(id)
+    }
+
+    pub(crate) fn push_behavior<A>(&self, behavior: Behavior<A>)
+    where
+        A: Actor<Msg = Msg>,
+    {
+        assert!(
+            TypeId::of::<A>() == self.typed.actor_type,
+            "behavior of another actor type set with `become` on {}",
+            self.cell.myself()
+        );
+
+        self.typed
+            .behaviors
+            .lock()
+            .unwrap()
+            .push(Some(Box::new(behavior)));
+    }
+
+    pub(crate) fn pop_behavior(&self) {
+        self.typed.behaviors.lock().unwrap().pop();
+    }
+
+    pub(crate) fn reset_behaviors(&self) {
+        self.typed.behaviors.lock().unwrap().clear();
+    }
+
+    /// Sets the future of the message being received, awaited by the mailbox.
+    pub(crate) fn set_pending(&self, pending: PendingRecv) {
+        *self.typed.pending.lock().unwrap() = Some(pending);
+    }
+
+    pub(crate) fn take_pending(&self) -> Option<PendingRecv> {
+        self.typed.pending.lock().unwrap().take()
+    }
+
+    /// Delivers a message to the current behavior, or to `recv` if there is none.
+    pub(crate) fn receive<A>(&self, actor: &mut A, ctx: &Context<Msg>, msg: Msg, sender: Sender)
+    where
+        A: Actor<Msg = Msg>,
+    {
+        // the behavior is taken out while it runs, since it may change the stack
+        let current = {
+            let mut behaviors = self.typed.behaviors.lock().unwrap();
+            let top = behaviors.len().checked_sub(1);
+            top.and_then(|i| behaviors[i].take().map(|b| (i, b)))
+        };
+
+        let mut running = match current {
+            Some((i, behavior)) => RunningBehavior {
+                behaviors: &self.typed.behaviors,
+                i,
+                behavior: Some(behavior),
+            },
+            None => return actor.recv(ctx, msg, sender),
+        };
+
+        // `become` only accepts behaviors of the actor's type
+        let behavior = running
+            .behavior
+            .as_mut()
+            .and_then(|b| b.downcast_mut::<Behavior<A>>())
+            .unwrap();
+        behavior(actor, ctx, msg, sender);
+    }
+
+    pub(crate) fn stash(&self, msg: Envelope<Msg>) -> Result<(), StashError<Msg>> {
+        let mut stash = self.typed.stash.lock().unwrap();
+        if stash.len() >= self.system().sys_settings().stash_capacity {
+            return Err(StashError::new(msg.msg));
+        }
+        stash.push(msg);
+        Ok(())
+    }
+
+    pub(crate) fn unstash_all(&self) {
+        let msgs = std::mem::take(&mut *self.typed.stash.lock().unwrap());
+        self.requeue(msgs);
+    }
+
+    /// Returns messages to the head of the mailbox, to be received before any other.
+    pub(crate) fn requeue(&self, msgs: Vec<Envelope<Msg>>) {
+        if msgs.is_empty() {
+            return;
+        }
+
+        if let Err(e) = self.typed.mailbox.enqueue_front(msgs) {
+            self.drop_stashed(e.msg, e.reason);
+        } else if !self.typed.mailbox.is_scheduled() {
+            self.typed.mailbox.set_scheduled(true);
+            self.cell.kernel().schedule();
+        }
+    }
+
+    /// Publishes all stashed messages to dead letters.
+    pub(crate) fn clear_stash(&self, reason: DeadLetterReason) {
+        let msgs = std::mem::take(&mut *self.typed.stash.lock().unwrap());
+        self.drop_stashed(msgs, reason);
+    }
+
+    fn drop_stashed(&self, msgs: Vec<Envelope<Msg>>, reason: DeadLetterReason) {
+        for msg in msgs {
+            self.dead_letter(&msg, reason);
+        }
+    }
+
+    /// Sends a message, waiting for space if the mailbox is full.
+    pub(crate) async fn send_msg_wait(&self, msg: Envelope<Msg>) -> MsgResult<Envelope<Msg>> {
+        let mb = &self.typed.mailbox;
+        let k = self.cell.kernel();
+
+        dispatch_wait(msg, mb, k).await.map_err(|e| {
+            self.dead_letter(&e.msg, e.reason);
             e
         })
     }
 
+    fn dead_letter(&self, msg: &Envelope<Msg>, reason: DeadLetterReason) {
+        if is_dead_letter(&msg.msg) {
+            return;
+        }
+
+        let msg = Envelope {
+            msg: format!("{:?}", msg.msg),
+            sender: msg.sender.clone(),
+            timer: None,
+        };
+        self.cell.dead_letter(msg, reason);
+    }
+
     pub(crate) fn send_sys_msg(&self, msg: Envelope<SystemMsg>) -> MsgResult<Envelope<SystemMsg>> {
         self.cell.send_sys_msg(msg)
     }
@@ -339,8 +967,8 @@ where
         &self.cell.inner.system
     }
 
-    pub(crate) fn handle_failure(&self, failed: BasicActorRef) {
-        self.cell.handle_failure(failed)
+    pub(crate) fn handle_failure(&self, failed: BasicActorRef, strategy: SupervisorStrategy) {
+        self.cell.handle_failure(failed, strategy)
     }
 
     pub(crate) fn receive_cmd<A: Actor>(&self, cmd: SystemCmd, actor: &mut Option<A>) {
@@ -393,6 +1021,200 @@ where
     pub fn myself(&self) -> ActorRef<Msg> {
         self.myself.clone()
     }
+
+    /// Sets a message aside to be received again after `unstash_all`.
+    ///
+    /// The number of stashed messages is limited by the `stash_capacity`
+    /// mailbox setting. Stashed messages are published to dead letters when the
+    /// actor stops, and when it restarts unless `Actor::keep_stash_on_restart`
+    /// returns `true`.
+    pub fn stash(&self, msg: Msg, sender: Sender) -> Result<(), StashError<Msg>> {
+        self.myself.cell.stash(Envelope {
+            msg,
+            sender,
+            timer: None,
+        })
+    }
+
+    /// Returns all stashed messages to the head of the mailbox, in the order they were stashed.
+    ///
+    /// They are received before any message that is already in the mailbox.
+    pub fn unstash_all(&self) {
+        self.myself.cell.unstash_all();
+    }
+
+    /// Replaces the message handler of the current actor with `behavior`.
+    ///
+    /// Messages are delivered to the most recent behavior instead of `recv`.
+    /// The previous behavior is kept on a stack and restored with `unbecome`.
+    /// The stack is cleared when the actor restarts.
+    ///
+    /// `A` must be the type of the current actor, otherwise this panics.
+    /// Named `r#become` because `become` is a reserved keyword.
+    pub fn r#become<A, F>(&self, behavior: F)
+    where
+        A: Actor<Msg = Msg>,
+        F: FnMut(&mut A, &Context<Msg>, Msg, Sender) + Send + 'static,
+    {
+        self.myself.cell.push_behavior::<A>(Box::new(behavior));
+    }
+
+    /// Restores the behavior that was replaced by the last `become`.
+    ///
+    /// Messages are delivered to `recv` again once all behaviors are removed.
+    pub fn unbecome(&self) {
+        self.myself.cell.pop_behavior();
+    }
+
+    /// Watches `actor` for termination.
+    ///
+    /// `SystemMsg::Terminated` is delivered to `sys_recv` exactly once when
+    /// the watched actor stops, or immediately if it has already stopped.
+    /// Watching the same actor again has no additional effect.
+    pub fn watch(&self, actor: impl Into<BasicActorRef>) {
+        self.myself.cell.cell.watch(actor.into(), None);
+    }
+
+    /// Watches `actor` for termination, delivering `msg` to `recv` instead of
+    /// `SystemMsg::Terminated`. The sender of `msg` is the terminated actor.
+    ///
+    /// Replaces the notification of a previous `watch` of the same actor.
+    pub fn watch_with<T>(&self, actor: impl Into<BasicActorRef>, msg: T)
+    where
+        T: Into<Msg>,
+    {
+        let msg = AnyMessage::new::<Msg>(msg.into(), true);
+        self.myself.cell.cell.watch(actor.into(), Some(msg));
+    }
+
+    /// Stops watching `actor`. No termination notification will be delivered
+    /// unless it was already sent.
+    pub fn unwatch(&self, actor: impl Into<BasicActorRef>) {
+        self.myself.cell.cell.unwatch(&actor.into());
+    }
+
+    /// Runs `future` on the system's runtime and sends its output,
+    /// converted by `map`, to the current actor as a normal message.
+    ///
+    /// The future is cancelled if the actor stops before it completes.
+    /// A message that can't be delivered is published to dead letters.
+    pub fn pipe_to_self<F, M>(&self, future: F, map: M)
+    where
+        F: Future + Send + 'static,
+        M: FnOnce(F::Output) -> Msg + Send + 'static,
+    {
+        let myself = self.myself.clone();
+        self.myself
+            .cell
+            .cell
+            .spawn_until_stopped(future, move |output| myself.send_msg(map(output), None));
+    }
+
+    /// Runs `future` on the system's runtime and sends its output to `actor`,
+    /// with the current actor as sender.
+    ///
+    /// The future is cancelled if the current actor stops before it completes.
+    /// A message that can't be delivered is published to dead letters.
+    pub fn pipe_to<F, T>(&self, future: F, actor: &ActorRef<T>)
+    where
+        F: Future + Send + 'static,
+        F::Output: Into<T>,
+        T: Message,
+    {
+        let actor = actor.clone();
+        let sender = Some(self.myself().into());
+        self.myself
+            .cell
+            .cell
+            .spawn_until_stopped(future, move |output| actor.send_msg(output.into(), sender));
+    }
+
+    /// Sends `msg` to the current actor after `delay`.
+    ///
+    /// Starting a timer with the `key` of an active timer cancels that timer.
+    /// The timer is cancelled when the actor stops or restarts.
+    /// A cancelled timer's messages that were not received yet are dropped.
+    pub fn start_timer<T>(&self, key: &str, delay: Duration, msg: T) -> ScheduleId
+    where
+        T: Into<Msg>,
+    {
+        let id = Uuid::new_v4();
+
+        let job = OnceJob {
+            id,
+            send_at: Instant::now() + delay,
+            receiver: self.myself().into(),
+            sender: None,
+            msg: AnyMessage::new::<Msg>(msg.into(), true),
+            owner: Some(self.myself().into()),
+        };
+
+        self.start_job(id, Some(key), Job::Once(job));
+        id
+    }
+
+    /// Sends `msg` to the current actor repeatedly, following `schedule`.
+    ///
+    /// Starting a timer with the `key` of an active timer cancels that timer.
+    /// The timer is cancelled when the actor stops or restarts.
+    /// A cancelled timer's messages that were not received yet are dropped.
+    pub fn start_repeat_timer<T>(&self, key: &str, schedule: RepeatSchedule, msg: T) -> ScheduleId
+    where
+        T: Into<Msg>,
+    {
+        let id = Uuid::new_v4();
+
+        let msg = AnyMessage::new::<Msg>(msg.into(), false);
+        let owner = Some(self.myself().into());
+        let job = schedule.into_job(id, self.myself().into(), None, msg, owner);
+
+        self.start_job(id, Some(key), Job::Repeat(Box::new(job)));
+        id
+    }
+
+    /// Cancels the timer started with `key`, returning `false` if there is no such active timer.
+    ///
+    /// Messages the timer sent that were not received yet are dropped.
+    pub fn cancel_timer(&self, key: &str) -> bool {
+        match self.myself.cell.cell.remove_keyed_timer(key) {
+            Some(id) => {
+                let _ = self.system.timer.lock().unwrap().send(Job::Cancel(id));
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The actors watched by the current actor.
+    ///
+    /// An actor is no longer watched once it terminated.
+    pub fn watching(&self) -> Vec<BasicActorRef> {
+        self.myself.cell.cell.watching()
+    }
+
+    /// The active timers started by the current actor, in the order they were started.
+    pub fn timers(&self) -> Vec<ActiveTimer> {
+        self.myself.cell.cell.timers()
+    }
+
+    /// Registers `job` as a timer of the current actor and sends it to the system's timer.
+    fn start_job(&self, id: ScheduleId, key: Option<&str>, job: Job) {
+        let replaced = self.myself.cell.cell.add_timer(ActiveTimer {
+            id,
+            key: key.map(String::from),
+        });
+
+        let timer = self.system.timer.lock().unwrap();
+        if let Some(replaced) = replaced {
+            let _ = timer.send(Job::Cancel(replaced));
+        }
+        let _ = timer.send(job);
+    }
+
+    fn handle(&self, id: ScheduleId) -> ScheduleHandle {
+        let owner = Some(self.myself().into());
+        ScheduleHandle::new(id, self.system.timer.clone(), owner)
+    }
 }
 
 impl<Msg: Message> ActorRefFactory for Context<Msg> {
@@ -443,18 +1265,41 @@ impl<Msg: Message> ActorRefFactory for Context<Msg> {
     }
 }
 
+impl<Msg: Message> AnonActorRefFactory for Context<Msg> {
+    fn actor_of_props_anon<A>(
+        &self,
+        props: BoxActorProd<A>,
+    ) -> Result<ActorRef<A::Msg>, CreateError>
+    where
+        A: Actor,
+    {
+        self.system
+            .provider
+            .create_anon_actor(props, &self.myself().into(), &self.system)
+    }
+}
+
+impl<Msg: Message> ActorSelectionFactory for Context<Msg> {
+    /// Selects actors by path, resolving a relative path from the current actor.
+    fn select(&self, path: &str) -> Result<ActorSelection, InvalidPath> {
+        ActorSelection::new(self.myself().into(), path)
+    }
+}
+
 impl<Msg> Timer for Context<Msg>
 where
     Msg: Message,
 {
-    fn schedule<T, M>(
+    /// Schedules `msg` as for the system, on behalf of the current actor.
+    ///
+    /// The schedule is cancelled when the current actor stops or restarts.
+    fn schedule_with<T, M>(
         &self,
-        initial_delay: Duration,
-        interval: Duration,
+        schedule: RepeatSchedule,
         receiver: ActorRef<M>,
         sender: Sender,
         msg: T,
-    ) -> ScheduleId
+    ) -> ScheduleHandle
     where
         T: Message + Into<M>,
         M: Message,
@@ -462,26 +1307,24 @@ where
         let id = Uuid::new_v4();
         let msg: M = msg.into();
 
-        let job = RepeatJob {
-            id,
-            send_at: Instant::now() + initial_delay,
-            interval,
-            receiver: receiver.into(),
-            sender,
-            msg: AnyMessage::new(msg, false),
-        };
+        let msg = AnyMessage::new(msg, false);
+        let owner = Some(self.myself().into());
+        let job = schedule.into_job(id, receiver.into(), sender, msg, owner);
 
-        let _ = self.system.timer.lock().unwrap().send(Job::Repeat(job));
-        id
+        self.start_job(id, None, Job::Repeat(Box::new(job)));
+        self.handle(id)
     }
 
+    /// Schedules `msg` as for the system, on behalf of the current actor.
+    ///
+    /// The schedule is cancelled when the current actor stops or restarts.
     fn schedule_once<T, M>(
         &self,
         delay: Duration,
         receiver: ActorRef<M>,
         sender: Sender,
         msg: T,
-    ) -> ScheduleId
+    ) -> ScheduleHandle
     where
         T: Message + Into<M>,
         M: Message,
@@ -495,13 +1338,45 @@ where
             receiver: receiver.into(),
             sender,
             msg: AnyMessage::new(msg, true),
+            owner: Some(self.myself().into()),
         };
 
-        let _ = self.system.timer.lock().unwrap().send(Job::Once(job));
-        id
+        self.start_job(id, None, Job::Once(job));
+        self.handle(id)
+    }
+
+    /// Schedules `msg` as for the system, on behalf of the current actor.
+    ///
+    /// The schedule is cancelled when the current actor stops or restarts.
+    fn schedule_cron<T, M>(
+        &self,
+        schedule: CronSchedule,
+        receiver: ActorRef<M>,
+        sender: Sender,
+        msg: T,
+    ) -> ScheduleHandle
+    where
+        T: Message + Into<M>,
+        M: Message,
+    {
+        let id = Uuid::new_v4();
+        let msg: M = msg.into();
+
+        let job = CronJob {
+            id,
+            schedule,
+            receiver: receiver.into(),
+            sender,
+            msg: AnyMessage::new(msg, false),
+            owner: Some(self.myself().into()),
+        };
+
+        self.start_job(id, None, Job::Cron(Box::new(job)));
+        self.handle(id)
     }
 
     fn cancel_schedule(&self, id: Uuid) {
+        self.myself.cell.cell.remove_timer(&id);
         let _ = self.system.timer.lock().unwrap().send(Job::Cancel(id));
     }
 }
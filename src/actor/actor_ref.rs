@@ -1,10 +1,11 @@
-use std::fmt;
+use std::{fmt, future::Future, time::Duration};
 
 use crate::{
     actor::{
         actor_cell::{ActorCell, ExtendedCell},
-        props::{ActorArgs, ActorFactory, ActorFactoryArgs},
-        Actor, ActorPath, ActorUri, BoxActorProd, CreateError,
+        ask::ask,
+        props::{ActorArgs, ActorFactory, ActorFactoryArgs, Props},
+        Actor, ActorPath, ActorUri, AskError, BoxActorProd, CreateError, SendError,
     },
     kernel::mailbox::AnyEnqueueError,
     system::SystemMsg,
@@ -60,6 +61,12 @@ pub type BoxedTell<T> = Box<dyn Tell<T> + Send + 'static>;
 pub trait Tell<T>: ActorReference + Send + 'static {
     fn tell(&self, msg: T, sender: Sender);
     fn box_clone(&self) -> BoxedTell<T>;
+
+    /// The untyped reference of the receiving actor, e.g. to watch it,
+    /// if there is one.
+    fn basic_ref(&self) -> Option<BasicActorRef> {
+        None
+    }
 }
 
 impl<T, M> Tell<T> for ActorRef<M>
@@ -74,6 +81,10 @@ where
     fn box_clone(&self) -> BoxedTell<T> {
         Box::new((*self).clone())
     }
+
+    fn basic_ref(&self) -> Option<BasicActorRef> {
+        Some(self.clone().into())
+    }
 }
 
 impl<T> ActorReference for BoxedTell<T>
@@ -182,8 +193,9 @@ impl BasicActorRef {
         ActorRef { cell }
     }
 
-    pub(crate) fn sys_init(&self) {
-        self.cell.kernel().sys_init();
+    /// Returns a typed reference to this actor if its message type is `Msg`.
+    pub fn downcast<Msg: Message>(&self) -> Option<ActorRef<Msg>> {
+        self.cell.typed().map(|cell| ActorRef { cell })
     }
 
     /// Send a message to this actor
@@ -255,7 +267,11 @@ impl ActorReference for BasicActorRef {
     }
 
     fn sys_tell(&self, msg: SystemMsg) {
-        let envelope = Envelope { msg, sender: None };
+        let envelope = Envelope {
+            msg,
+            sender: None,
+            timer: None,
+        };
         let _ = self.cell.send_sys_msg(envelope);
     }
 }
@@ -306,7 +322,11 @@ impl ActorReference for &BasicActorRef {
     }
 
     fn sys_tell(&self, msg: SystemMsg) {
-        let envelope = Envelope { msg, sender: None };
+        let envelope = Envelope {
+            msg,
+            sender: None,
+            timer: None,
+        };
         let _ = self.cell.send_sys_msg(envelope);
     }
 }
@@ -338,6 +358,21 @@ where
     }
 }
 
+impl<Msg> From<&ActorRef<Msg>> for BasicActorRef
+where
+    Msg: Message,
+{
+    fn from(actor: &ActorRef<Msg>) -> BasicActorRef {
+        BasicActorRef::from(actor.clone())
+    }
+}
+
+impl From<&BasicActorRef> for BasicActorRef {
+    fn from(actor: &BasicActorRef) -> BasicActorRef {
+        actor.clone()
+    }
+}
+
 impl<Msg> From<ActorRef<Msg>> for Option<BasicActorRef>
 where
     Msg: Message,
@@ -382,10 +417,71 @@ impl<Msg: Message> ActorRef<Msg> {
         let envelope = Envelope {
             msg,
             sender: sender.into(),
+            timer: None,
         };
         // consume the result (we don't return it to user)
         let _ = self.cell.send_msg(envelope);
     }
+
+    /// Send a message to this actor
+    ///
+    /// Returns an error with the message if this actor is terminated, or if
+    /// its mailbox is full and its overflow policy is `OverflowPolicy::Reject`.
+    /// Messages that are not sent are also published to dead letters.
+    pub fn try_tell<T>(
+        &self,
+        msg: T,
+        sender: impl Into<Option<BasicActorRef>>,
+    ) -> Result<(), SendError<Msg>>
+    where
+        T: Into<Msg>,
+    {
+        let envelope = Envelope {
+            msg: msg.into(),
+            sender: sender.into(),
+            timer: None,
+        };
+        self.cell.send_msg(envelope).map_err(SendError::from)
+    }
+
+    /// Send a message to this actor, waiting until there is space in its mailbox
+    ///
+    /// The overflow policy of a bounded mailbox is not applied.
+    /// Returns an error with the message if this actor is terminated.
+    pub async fn send<T>(
+        &self,
+        msg: T,
+        sender: impl Into<Option<BasicActorRef>>,
+    ) -> Result<(), SendError<Msg>>
+    where
+        T: Into<Msg>,
+    {
+        let envelope = Envelope {
+            msg: msg.into(),
+            sender: sender.into(),
+            timer: None,
+        };
+        self.cell
+            .send_msg_wait(envelope)
+            .await
+            .map_err(SendError::from)
+    }
+
+    /// Send a message to this actor and wait for a reply
+    ///
+    /// The message is sent immediately with a temporary actor as its sender.
+    /// The returned future resolves to the first message of type `R` sent
+    /// back to that sender, or to an error if no reply arrives within
+    /// `timeout` or this actor terminates before replying.
+    ///
+    /// The future must be polled on a tokio runtime with the time driver enabled.
+    pub fn ask<T, R>(&self, msg: T, timeout: Duration) -> impl Future<Output = Result<R, AskError>>
+    where
+        T: Into<Msg>,
+        R: Message,
+    {
+        ask(self, msg.into(), timeout)
+    }
 }
 
 impl<Msg: Message> ActorReference for ActorRef<Msg> {
@@ -434,7 +530,11 @@ impl<Msg: Message> ActorReference for ActorRef<Msg> {
     }
 
     fn sys_tell(&self, msg: SystemMsg) {
-        let envelope = Envelope { msg, sender: None };
+        let envelope = Envelope {
+            msg,
+            sender: None,
+            timer: None,
+        };
         let _ = self.cell.send_sys_msg(envelope);
     }
 }
@@ -485,7 +585,11 @@ impl<Msg: Message> ActorReference for &ActorRef<Msg> {
     }
 
     fn sys_tell(&self, msg: SystemMsg) {
-        let envelope = Envelope { msg, sender: None };
+        let envelope = Envelope {
+            msg,
+            sender: None,
+            timer: None,
+        };
         let _ = self.cell.send_sys_msg(envelope);
     }
 }
@@ -538,3 +642,37 @@ pub trait ActorRefFactory {
 
     fn stop(&self, actor: impl ActorReference);
 }
+
+/// Produces `ActorRef`s of actors with generated names, such as `$b4`,
+/// that are unique among all actors of the system.
+///
+/// Names starting with `$` are reserved for these actors.
+pub trait AnonActorRefFactory: ActorRefFactory {
+    /// Creates an actor with a generated name.
+    fn actor_of_props_anon<A>(
+        &self,
+        props: BoxActorProd<A>,
+    ) -> Result<ActorRef<A::Msg>, CreateError>
+    where
+        A: Actor;
+
+    /// Creates an actor with a generated name. See `actor_of_props_anon`.
+    fn actor_of_anon<A>(&self) -> Result<ActorRef<<A as Actor>::Msg>, CreateError>
+    where
+        A: ActorFactory + Actor,
+    {
+        self.actor_of_props_anon(Props::new::<A>())
+    }
+
+    /// Creates an actor with a generated name. See `actor_of_props_anon`.
+    fn actor_of_args_anon<A, Args>(
+        &self,
+        args: Args,
+    ) -> Result<ActorRef<<A as Actor>::Msg>, CreateError>
+    where
+        Args: ActorArgs,
+        A: ActorFactoryArgs<Args>,
+    {
+        self.actor_of_props_anon(Props::new_args::<A, _>(args))
+    }
+}
@@ -0,0 +1,123 @@
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::sync::oneshot;
+
+use crate::{
+    actor::{
+        Actor, ActorFactoryArgs, ActorRef, ActorRefFactory, AskError, BasicActorRef, Context,
+        DeadLetterReason, Props, Sender,
+    },
+    system::{ActorSystem, SystemMsg},
+    Envelope, Message,
+};
+
+type ReplyTx<R> = Arc<Mutex<Option<oneshot::Sender<R>>>>;
+
+/// Sends `msg` to `target` and returns a future that resolves to the first reply.
+///
+/// The reply target is a temporary actor created under the `/temp` guardian.
+/// It is stopped once the future completes, times out or is dropped.
+pub(crate) fn ask<Msg, R>(
+    target: &ActorRef<Msg>,
+    msg: Msg,
+    timeout: Duration,
+) -> impl Future<Output = Result<R, AskError>>
+where
+    Msg: Message,
+    R: Message,
+{
+    let pending = start(target, msg);
+
+    async move {
+        let (guard, rx) = pending?;
+        let result = match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(reply)) => Ok(reply),
+            Ok(Err(_)) => Err(AskError::Terminated),
+            Err(_) => Err(AskError::Timeout),
+        };
+        drop(guard);
+        result
+    }
+}
+
+fn start<Msg, R>(
+    target: &ActorRef<Msg>,
+    msg: Msg,
+) -> Result<(AskGuard, oneshot::Receiver<R>), AskError>
+where
+    Msg: Message,
+    R: Message,
+{
+    let sys = target.cell.system().clone();
+    let (tx, rx) = oneshot::channel();
+
+    let props = Props::new_args::<AskActor<R>, _>(Arc::new(Mutex::new(Some(tx))));
+    let reply_to: BasicActorRef = sys
+        .provider
+        .create_anon_actor(props, sys.temp_root(), &sys)?
+        .into();
+
+    // watch the target before sending so that a termination
+    // caused by this very message is not missed
+    reply_to.cell.watch(target.clone().into(), None);
+
+    let guard = AskGuard { sys, reply_to };
+
+    let envelope = Envelope {
+        msg,
+        sender: Some(guard.reply_to.clone()),
+        timer: None,
+    };
+    target.cell.send_msg(envelope).map_err(|e| match e.reason {
+        DeadLetterReason::MailboxFull => AskError::MailboxFull,
+        DeadLetterReason::Terminated
+        | DeadLetterReason::Restarted
+        | DeadLetterReason::NoRoutees => AskError::Terminated,
+    })?;
+
+    Ok((guard, rx))
+}
+
+/// Stops the temporary actor when the ask completes, in any way.
+struct AskGuard {
+    sys: ActorSystem,
+    reply_to: BasicActorRef,
+}
+
+impl Drop for AskGuard {
+    fn drop(&mut self) {
+        self.sys.stop(&self.reply_to);
+    }
+}
+
+/// Temporary actor receiving the reply of an `ask`.
+struct AskActor<R: Message> {
+    tx: ReplyTx<R>,
+}
+
+impl<R: Message> ActorFactoryArgs<ReplyTx<R>> for AskActor<R> {
+    fn create_args(tx: ReplyTx<R>) -> Self {
+        AskActor { tx }
+    }
+}
+
+impl<R: Message> Actor for AskActor<R> {
+    type Msg = R;
+
+    fn sys_recv(&mut self, _: &Context<R>, msg: SystemMsg, _: Sender) {
+        if let SystemMsg::Terminated(_) = msg {
+            // dropping the sender resolves the ask with `AskError::Terminated`
+            self.tx.lock().unwrap().take();
+        }
+    }
+
+    fn recv(&mut self, _: &Context<R>, msg: R, _: Sender) {
+        if let Some(tx) = self.tx.lock().unwrap().take() {
+            let _ = tx.send(msg);
+        }
+    }
+}
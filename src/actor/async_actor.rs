@@ -0,0 +1,267 @@
+use std::{
+    any::Any,
+    future::Future,
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context as TaskContext, Poll},
+};
+
+use crate::{
+    actor::{Actor, Context, Sender, SupervisorStrategy},
+    system::SystemMsg,
+    Envelope, Message,
+};
+
+/// A boxed future that can be sent between threads, returned by `AsyncActor::recv`.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// The future of a message being received by an `Async` actor,
+/// resolving to the panic that interrupted it, if any.
+pub(crate) type PendingRecv = BoxFuture<'static, Result<(), Box<dyn Any + Send>>>;
+
+/// An actor whose message handler returns a future.
+///
+/// The future is awaited before the next message is dequeued, so the
+/// guarantee that only one message is processed at any one time still holds.
+/// A panic inside the future fails the actor the same way as a panic in
+/// `Actor::recv`, following its supervisor's strategy.
+///
+/// An `AsyncActor` is started wrapped in `Async`.
+///
+/// # Examples
+///
+/// ```
+/// # use tezedge_actor_system::actors::*;
+/// # use std::time::Duration;
+///
+/// #[derive(Default)]
+/// struct Fetcher;
+///
+/// impl AsyncActor for Fetcher {
+///     type Msg = u64;
+///
+///     fn recv<'a>(
+///         &'a mut self,
+///         ctx: &'a Context<Self::Msg>,
+///         delay: Self::Msg,
+///         _sender: Sender,
+///     ) -> BoxFuture<'a, ()> {
+///         Box::pin(async move {
+///             tokio::time::sleep(Duration::from_millis(delay)).await;
+///             println!("{} waited {}ms", ctx.myself.name(), delay);
+///         })
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let backend = tokio::runtime::Handle::current().into();
+/// let sys = ActorSystem::new(backend).unwrap();
+///
+/// let actor = sys.actor_of::<Async<Fetcher>>("fetcher").unwrap();
+/// actor.tell(10u64, None);
+/// # }
+/// ```
+#[allow(unused_variables)]
+pub trait AsyncActor: Send + 'static {
+    type Msg: Message;
+
+    /// Invoked when an actor is being started by the system.
+    ///
+    /// See `Actor::pre_start`.
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {}
+
+    /// Invoked after an actor has started.
+    ///
+    /// See `Actor::post_start`.
+    fn post_start(&mut self, ctx: &Context<Self::Msg>) {}
+
+    /// Invoked after an actor has been stopped.
+    ///
+    /// Not invoked if the actor is stopped while a future returned by `recv` is pending.
+    fn post_stop(&mut self) {}
+
+    /// Invoked when an actor receives a system message
+    ///
+    /// System messages received while a future returned by `recv` is pending
+    /// are delivered once it has completed.
+    fn sys_recv(&mut self, ctx: &Context<Self::Msg>, msg: SystemMsg, sender: Sender) {}
+
+    /// Invoked when an actor receives a message
+    ///
+    /// The next message is received once the returned future has completed,
+    /// even if the actor was resumed or restarted meanwhile.
+    fn recv<'a>(
+        &'a mut self,
+        ctx: &'a Context<Self::Msg>,
+        msg: Self::Msg,
+        sender: Sender,
+    ) -> BoxFuture<'a, ()>;
+
+    /// Return a supervisor strategy that will be used when handling failed child actors.
+    ///
+    /// While a future returned by `recv` is pending, the strategy returned
+    /// when the actor was created is used.
+    fn supervisor_strategy(&self) -> SupervisorStrategy {
+        SupervisorStrategy::default()
+    }
+
+    /// Return `true` to keep stashed messages when this actor is restarted.
+    ///
+    /// See `Actor::keep_stash_on_restart`.
+    fn keep_stash_on_restart(&self) -> bool {
+        false
+    }
+}
+
+/// Runs an `AsyncActor` as an `Actor`.
+///
+/// Create it with `Async::new`, for example in `Props::new_from_args`,
+/// or start it with `actor_of::<Async<A>>` if `A` implements `Default`.
+pub struct Async<A: AsyncActor> {
+    state: Arc<Mutex<AsyncState<A>>>,
+    strategy: SupervisorStrategy,
+}
+
+struct AsyncState<A: AsyncActor> {
+    /// `None` while a future returned by `recv` owns the actor
+    actor: Option<A>,
+    deferred: Vec<(SystemMsg, Sender)>,
+    queued: Vec<Envelope<A::Msg>>,
+}
+
+impl<A: AsyncActor> Async<A> {
+    pub fn new(actor: A) -> Self {
+        Async {
+            strategy: actor.supervisor_strategy(),
+            state: Arc::new(Mutex::new(AsyncState {
+                actor: Some(actor),
+                deferred: Vec::new(),
+                queued: Vec::new(),
+            })),
+        }
+    }
+
+    fn with_actor<F: FnOnce(&mut A)>(&self, f: F) {
+        if let Some(actor) = self.state.lock().unwrap().actor.as_mut() {
+            f(actor);
+        }
+    }
+}
+
+impl<A: AsyncActor + Default> Default for Async<A> {
+    fn default() -> Self {
+        Async::new(A::default())
+    }
+}
+
+impl<A: AsyncActor> Actor for Async<A> {
+    type Msg = A::Msg;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        self.with_actor(|actor| actor.pre_start(ctx));
+    }
+
+    fn post_start(&mut self, ctx: &Context<Self::Msg>) {
+        self.with_actor(|actor| actor.post_start(ctx));
+    }
+
+    fn post_stop(&mut self) {
+        self.with_actor(|actor| actor.post_stop());
+    }
+
+    fn sys_recv(&mut self, ctx: &Context<Self::Msg>, msg: SystemMsg, sender: Sender) {
+        let mut state = self.state.lock().unwrap();
+        match state.actor.as_mut() {
+            Some(actor) => actor.sys_recv(ctx, msg, sender),
+            None => state.deferred.push((msg, sender)),
+        }
+    }
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
+        let mut actor = {
+            let mut state = self.state.lock().unwrap();
+            match state.actor.take() {
+                Some(actor) => actor,
+                None => {
+                    // the mailbox was resumed while a future is pending
+                    state.queued.push(Envelope {
+                        msg,
+                        sender,
+                        timer: None,
+                    });
+                    return;
+                }
+            }
+        };
+
+        let state = self.state.clone();
+        let owned_ctx = Context {
+            myself: ctx.myself.clone(),
+            system: ctx.system.clone(),
+            kernel: ctx.kernel.clone(),
+        };
+
+        let pending = async move {
+            let ctx = owned_ctx;
+            let result = CatchUnwind(actor.recv(&ctx, msg, sender)).await;
+
+            // the actor is kept even if it panicked,
+            // since the supervisor may decide to resume it
+            let mut state = state.lock().unwrap();
+            let deferred = std::mem::take(&mut state.deferred);
+            let delivered = catch_unwind(AssertUnwindSafe(|| {
+                for (msg, sender) in deferred {
+                    actor.sys_recv(&ctx, msg, sender);
+                }
+            }));
+            state.actor = Some(actor);
+
+            // messages received meanwhile come next, before the rest of the mailbox
+            let queued = std::mem::take(&mut state.queued);
+            drop(state);
+            ctx.myself.cell.requeue(queued);
+
+            result.and(delivered)
+        };
+
+        // the mailbox awaits it before dequeuing the next message
+        ctx.myself.cell.set_pending(Box::pin(pending));
+    }
+
+    fn supervisor_strategy(&self) -> SupervisorStrategy {
+        match self.state.lock().unwrap().actor.as_ref() {
+            Some(actor) => actor.supervisor_strategy(),
+            None => self.strategy.clone(),
+        }
+    }
+
+    fn keep_stash_on_restart(&self) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .actor
+            .as_ref()
+            .is_some_and(|actor| actor.keep_stash_on_restart())
+    }
+}
+
+/// Polls a future, catching a panic while it is polled.
+struct CatchUnwind<F>(F);
+
+impl<F> Future for CatchUnwind<F>
+where
+    F: Future + Unpin,
+{
+    type Output = Result<F::Output, Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let future = &mut self.0;
+        match catch_unwind(AssertUnwindSafe(|| Pin::new(&mut *future).poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 #![allow(unused_variables)]
 
-use std::{collections::HashMap, hash::Hash};
+use std::{any::Any, collections::HashMap, hash::Hash};
 
 use crate::{
     actor::{
@@ -21,6 +21,9 @@ pub type ChannelCtx<Msg> = Context<ChannelMsg<Msg>>;
 pub type ChannelRef<Msg> = ActorRef<ChannelMsg<Msg>>;
 
 /// A specialized actor for providing Publish/Subscribe capabilities for user level messages
+///
+/// The channel watches the actors of its subscribers, dropping all
+/// subscriptions of an actor once it is terminated.
 pub struct Channel<Msg: Message> {
     subs: Subs<Msg>,
 }
@@ -39,31 +42,15 @@ where
 {
     type Msg = ChannelMsg<Msg>;
 
-    // todo subscribe to events to unsub subscribers when they die
-    fn pre_start(&mut self, ctx: &ChannelCtx<Msg>) {
-        // let sub = Subscribe {
-        //     topic: SysTopic::ActorTerminated.into(),
-        //     actor: Box::new(ctx.myself.clone())//.into()
-        // };
-
-        // let msg = ChannelMsg::Subscribe(sub);
-        // ctx.myself.tell(msg, None);
-    }
-
     fn recv(&mut self, ctx: &ChannelCtx<Msg>, msg: ChannelMsg<Msg>, sender: Sender) {
         self.receive(ctx, msg, sender);
     }
 
-    // We expect to receive ActorTerminated messages because we subscribed
-    // to this system event. This allows us to remove actors that have been
-    // terminated but did not explicity unsubscribe before terminating.
+    // Subscribers are watched, so that actors that terminated without
+    // explicitly unsubscribing are removed.
     fn sys_recv(&mut self, _: &ChannelCtx<Msg>, msg: SystemMsg, sender: Sender) {
-        if let SystemMsg::Event(SystemEvent::ActorTerminated(terminated)) = msg {
-            let subs = self.subs.clone();
-
-            for topic in subs.keys() {
-                unsubscribe(&mut self.subs, topic, &terminated.actor);
-            }
+        if let SystemMsg::Terminated(actor) = msg {
+            remove_terminated(&mut self.subs, &actor);
         }
     }
 }
@@ -92,6 +79,7 @@ where
     type Msg = ChannelMsg<Msg>;
 
     fn receive(&mut self, ctx: &ChannelCtx<Msg>, msg: Subscribe<Msg>, sender: Sender) {
+        watch(ctx, &msg.actor);
         let subs = self.subs.entry(msg.topic).or_default();
         subs.push(msg.actor);
     }
@@ -109,6 +97,7 @@ where
         mut msg: SubscribeWithResponse<Msg>,
         sender: Sender,
     ) {
+        watch(ctx, &msg.actor);
         let subs = self.subs.entry(msg.topic.clone()).or_default();
         subs.push(msg.actor.clone());
 
@@ -137,6 +126,10 @@ where
 
     fn receive(&mut self, ctx: &ChannelCtx<Msg>, msg: Unsubscribe<Msg>, sender: Sender) {
         unsubscribe(&mut self.subs, &msg.topic, &msg.actor);
+
+        if !is_subscribed(&self.subs, &msg.actor) {
+            unwatch(ctx, &msg.actor);
+        }
     }
 }
 
@@ -147,11 +140,8 @@ where
     type Msg = ChannelMsg<Msg>;
 
     fn receive(&mut self, ctx: &ChannelCtx<Msg>, msg: UnsubscribeAll<Msg>, sender: Sender) {
-        let subs = self.subs.clone();
-
-        for topic in subs.keys() {
-            unsubscribe(&mut self.subs, topic, &msg.actor);
-        }
+        unsubscribe_all(&mut self.subs, &msg.actor);
+        unwatch(ctx, &msg.actor);
     }
 }
 
@@ -178,20 +168,62 @@ where
     }
 }
 
-fn unsubscribe<Msg>(subs: &mut Subs<Msg>, topic: &Topic, actor: &dyn ActorReference) {
-    // Nightly only: self.subs.get(msg_type).unwrap().remove_item(actor);
-    if subs.contains_key(topic) {
-        if let Some(pos) = subs
-            .get(topic)
-            .unwrap()
-            .iter()
-            .position(|x| x.path() == actor.path())
-        {
-            subs.get_mut(topic).unwrap().remove(pos);
+fn unsubscribe<Msg: Message>(subs: &mut Subs<Msg>, topic: &Topic, actor: &BoxedTell<Msg>) {
+    if let Some(topic_subs) = subs.get_mut(topic) {
+        if let Some(pos) = topic_subs.iter().position(|sub| is_same_sub(sub, actor)) {
+            topic_subs.remove(pos);
         }
     }
 }
 
+/// Removes all subscriptions of `actor`.
+fn unsubscribe_all<Msg: Message>(subs: &mut Subs<Msg>, actor: &BoxedTell<Msg>) {
+    for topic_subs in subs.values_mut() {
+        topic_subs.retain(|sub| !is_same_sub(sub, actor));
+    }
+    subs.retain(|_, topic_subs| !topic_subs.is_empty());
+}
+
+/// Removes all subscriptions of the terminated `actor`, keeping those of
+/// a new actor at the same path that subscribed before the notification.
+fn remove_terminated<Msg: Message>(subs: &mut Subs<Msg>, actor: &BasicActorRef) {
+    for topic_subs in subs.values_mut() {
+        topic_subs.retain(|sub| match sub.basic_ref() {
+            Some(sub) => !sub.cell.is_same(&actor.cell),
+            None => true,
+        });
+    }
+    subs.retain(|_, topic_subs| !topic_subs.is_empty());
+}
+
+/// Watches the actor of a subscriber, if it is backed by one.
+fn watch<Msg: Message>(ctx: &ChannelCtx<Msg>, sub: &BoxedTell<Msg>) {
+    if let Some(actor) = sub.basic_ref() {
+        ctx.watch(actor);
+    }
+}
+
+fn unwatch<Msg: Message>(ctx: &ChannelCtx<Msg>, sub: &BoxedTell<Msg>) {
+    if let Some(actor) = sub.basic_ref() {
+        ctx.unwatch(actor);
+    }
+}
+
+fn is_subscribed<Msg: Message>(subs: &Subs<Msg>, actor: &BoxedTell<Msg>) -> bool {
+    subs.values().flatten().any(|sub| is_same_sub(sub, actor))
+}
+
+/// Whether two subscribers are the same actor instance, rather than
+/// actors at the same path. Subscribers not backed by an actor are
+/// compared by path.
+fn is_same_sub<Msg: Message>(a: &BoxedTell<Msg>, b: &BoxedTell<Msg>) -> bool {
+    match (a.basic_ref(), b.basic_ref()) {
+        (Some(a), Some(b)) => a.cell.is_same(&b.cell),
+        (None, None) => a.path() == b.path(),
+        _ => false,
+    }
+}
+
 /// A specialized channel that publishes messages as system messages
 #[derive(Default)]
 pub struct EventsChannel(Channel<SystemEvent>);
@@ -199,10 +231,6 @@ pub struct EventsChannel(Channel<SystemEvent>);
 impl Actor for EventsChannel {
     type Msg = ChannelMsg<SystemEvent>;
 
-    fn pre_start(&mut self, ctx: &ChannelCtx<SystemEvent>) {
-        self.0.pre_start(ctx);
-    }
-
     fn recv(
         &mut self,
         ctx: &ChannelCtx<SystemEvent>,
@@ -268,6 +296,30 @@ pub struct DeadLetter {
     pub msg: String,
     pub sender: Sender,
     pub recipient: BasicActorRef,
+    pub reason: DeadLetterReason,
+}
+
+/// Whether `msg` is itself a dead letter, which is dropped if it can't be delivered
+/// rather than published again, e.g. once the dead letters channel has stopped.
+pub(crate) fn is_dead_letter<Msg: Message>(msg: &Msg) -> bool {
+    let msg: &dyn Any = msg;
+    msg.is::<DeadLetter>() || msg.is::<DLChannelMsg>()
+}
+
+/// Why a message could not be delivered
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeadLetterReason {
+    /// The recipient was terminated
+    Terminated,
+
+    /// The recipient's bounded mailbox was full
+    MailboxFull,
+
+    /// The recipient stashed the message and was restarted
+    Restarted,
+
+    /// The recipient is a router without routees
+    NoRoutees,
 }
 
 #[derive(Debug, Clone)]
@@ -4,7 +4,10 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use crate::actor::Actor;
+use crate::{
+    actor::Actor,
+    kernel::mailbox::{MailboxCapacity, MailboxKind},
+};
 
 /// Provides instances of `ActorProducer` for use when creating Actors (`actor_of_props`).
 ///
@@ -46,7 +49,7 @@ impl Props {
     ///
     ///     // start the actor and get an `ActorRef`
     ///     let actor = sys.actor_of_props("user", props).unwrap();
-    ///     sys.shutdown().await
+    ///     sys.shutdown().await;
     /// }
     /// ```
     #[inline]
@@ -90,7 +93,7 @@ impl Props {
     ///     let props = Props::new_from_args(User::actor, "Naomi Nagata".into());
     ///
     ///     let actor = sys.actor_of_props("user", props).unwrap();
-    ///     sys.shutdown().await
+    ///     sys.shutdown().await;
     /// }
     /// ```
     /// An actor requiring multiple parameters.
@@ -126,7 +129,7 @@ impl Props {
     ///
     ///     // start the actor and get an `ActorRef`
     ///     let actor = sys.actor_of_props("bank_account", props).unwrap();
-    ///     sys.shutdown().await
+    ///     sys.shutdown().await;
     /// }
     /// ```
     #[inline]
@@ -166,7 +169,7 @@ impl Props {
     ///
     ///     // start the actor and get an `ActorRef`
     ///     let actor = sys.actor_of_props("user", props).unwrap();
-    ///     sys.shutdown().await
+    ///     sys.shutdown().await;
     /// }
     /// ```
     /// Creates an `ActorProducer` from a type which implements ActorFactory with no factory method parameters.
@@ -198,7 +201,7 @@ impl Props {
     ///
     ///     // start the actor and get an `ActorRef`
     ///     let actor = sys.actor_of_props("user", props).unwrap();
-    ///     sys.shutdown().await
+    ///     sys.shutdown().await;
     /// }
     /// ```
     #[inline]
@@ -241,7 +244,7 @@ impl Props {
     ///     let props = Props::new_args::<User, _>("Naomi Nagata".into());
     ///
     ///     let actor = sys.actor_of_props("user", props).unwrap();
-    ///     sys.shutdown().await
+    ///     sys.shutdown().await;
     /// }
     /// ```
     /// An actor requiring multiple parameters.
@@ -277,7 +280,7 @@ impl Props {
     ///
     ///     // start the actor and get an `ActorRef`
     ///     let actor = sys.actor_of_props("bank_account", props).unwrap();
-    ///     sys.shutdown().await
+    ///     sys.shutdown().await;
     /// }
     /// ```
     #[inline]
@@ -288,6 +291,106 @@ impl Props {
     {
         Self::new_from_args(A::create_args, args)
     }
+
+    /// Creates an `ActorProducer` that starts actors produced by `props`
+    /// with a mailbox of the given `capacity`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tezedge_actor_system::actors::*;
+    ///
+    /// #[derive(Default)]
+    /// struct User;
+    ///
+    /// # impl Actor for User {
+    /// #    type Msg = String;
+    /// #    fn recv(&mut self, _ctx: &Context<String>, _msg: String, _sender: Sender) {}
+    /// # }
+    /// // main
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let backend = tokio::runtime::Handle::current().into();
+    ///     let sys = ActorSystem::new(backend).unwrap();
+    ///
+    ///     let capacity = MailboxCapacity::bounded(1000, OverflowPolicy::DropOldest);
+    ///     let props = Props::with_mailbox(Props::new::<User>(), capacity);
+    ///
+    ///     // start the actor and get an `ActorRef`
+    ///     let actor = sys.actor_of_props("user", props).unwrap();
+    ///     sys.shutdown().await;
+    /// }
+    /// ```
+    #[inline]
+    pub fn with_mailbox<A>(
+        props: BoxActorProd<A>,
+        capacity: MailboxCapacity,
+    ) -> Arc<Mutex<impl ActorProducer<Actor = A>>>
+    where
+        A: Actor + Send + 'static,
+    {
+        Arc::new(Mutex::new(ActorPropsWithMailbox {
+            props,
+            capacity: Some(capacity),
+            kind: None,
+        }))
+    }
+
+    /// Creates an `ActorProducer` that starts actors produced by `props`
+    /// with a mailbox of the given `kind`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tezedge_actor_system::actors::*;
+    ///
+    /// #[derive(Default)]
+    /// struct Worker;
+    ///
+    /// #[derive(Clone, Debug)]
+    /// enum WorkerMsg {
+    ///     Job(u32),
+    ///     Cancel,
+    /// }
+    ///
+    /// impl ControlMessage for WorkerMsg {
+    ///     fn is_control(&self) -> bool {
+    ///         matches!(self, WorkerMsg::Cancel)
+    ///     }
+    /// }
+    ///
+    /// # impl Actor for Worker {
+    /// #    type Msg = WorkerMsg;
+    /// #    fn recv(&mut self, _ctx: &Context<WorkerMsg>, _msg: WorkerMsg, _sender: Sender) {}
+    /// # }
+    /// // main
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let backend = tokio::runtime::Handle::current().into();
+    ///     let sys = ActorSystem::new(backend).unwrap();
+    ///
+    ///     // `Cancel` is received before any queued `Job`
+    ///     let props = Props::with_mailbox_kind(Props::new::<Worker>(), MailboxKind::control_aware());
+    ///
+    ///     // start the actor and get an `ActorRef`
+    ///     let actor = sys.actor_of_props("worker", props).unwrap();
+    ///     sys.shutdown().await;
+    /// }
+    /// ```
+    #[inline]
+    pub fn with_mailbox_kind<A>(
+        props: BoxActorProd<A>,
+        kind: MailboxKind<A::Msg>,
+    ) -> Arc<Mutex<impl ActorProducer<Actor = A>>>
+    where
+        A: Actor + Send + 'static,
+    {
+        Arc::new(Mutex::new(ActorPropsWithMailbox {
+            props,
+            capacity: None,
+            kind: Some(kind),
+        }))
+    }
 }
 
 /// A `Clone`, `Send` and `Sync` `ActorProducer`
@@ -332,6 +435,20 @@ pub trait ActorProducer: fmt::Debug + Send + UnwindSafe + RefUnwindSafe {
     /// If the provided factory method panics the panic will be caught
     /// by the system, resulting in an error result returning to `actor_of_props`.
     fn produce(&self) -> Self::Actor;
+
+    /// The capacity of the mailbox of produced actors.
+    ///
+    /// Unbounded unless set with `Props::with_mailbox`.
+    fn mailbox_capacity(&self) -> MailboxCapacity {
+        MailboxCapacity::Unbounded
+    }
+
+    /// The kind of mailbox of produced actors.
+    ///
+    /// FIFO unless set with `Props::with_mailbox_kind`.
+    fn mailbox_kind(&self) -> MailboxKind<<Self::Actor as Actor>::Msg> {
+        MailboxKind::Fifo
+    }
 }
 
 impl<A> ActorProducer for Arc<Mutex<Box<dyn ActorProducer<Actor = A>>>>
@@ -343,6 +460,14 @@ where
     fn produce(&self) -> A {
         self.lock().unwrap().produce()
     }
+
+    fn mailbox_capacity(&self) -> MailboxCapacity {
+        self.lock().unwrap().mailbox_capacity()
+    }
+
+    fn mailbox_kind(&self) -> MailboxKind<A::Msg> {
+        self.lock().unwrap().mailbox_kind()
+    }
 }
 
 impl<A> ActorProducer for Arc<Mutex<dyn ActorProducer<Actor = A>>>
@@ -354,6 +479,14 @@ where
     fn produce(&self) -> A {
         self.lock().unwrap().produce()
     }
+
+    fn mailbox_capacity(&self) -> MailboxCapacity {
+        self.lock().unwrap().mailbox_capacity()
+    }
+
+    fn mailbox_kind(&self) -> MailboxKind<A::Msg> {
+        self.lock().unwrap().mailbox_kind()
+    }
 }
 
 impl<A> ActorProducer for Box<dyn ActorProducer<Actor = A>>
@@ -365,6 +498,14 @@ where
     fn produce(&self) -> A {
         (**self).produce()
     }
+
+    fn mailbox_capacity(&self) -> MailboxCapacity {
+        (**self).mailbox_capacity()
+    }
+
+    fn mailbox_kind(&self) -> MailboxKind<A::Msg> {
+        (**self).mailbox_kind()
+    }
 }
 
 pub struct ActorProps<A: Actor> {
@@ -462,5 +603,49 @@ impl<A: Actor, Args: ActorArgs> fmt::Debug for ActorPropsWithArgs<A, Args> {
     }
 }
 
+/// Overrides the mailbox settings of `props` that are `Some`
+pub struct ActorPropsWithMailbox<A: Actor> {
+    props: BoxActorProd<A>,
+    capacity: Option<MailboxCapacity>,
+    kind: Option<MailboxKind<A::Msg>>,
+}
+
+impl<A: Actor> UnwindSafe for ActorPropsWithMailbox<A> {}
+impl<A: Actor> RefUnwindSafe for ActorPropsWithMailbox<A> {}
+
+impl<A> ActorProducer for ActorPropsWithMailbox<A>
+where
+    A: Actor + Send + 'static,
+{
+    type Actor = A;
+
+    fn produce(&self) -> A {
+        self.props.produce()
+    }
+
+    fn mailbox_capacity(&self) -> MailboxCapacity {
+        self.capacity
+            .unwrap_or_else(|| self.props.mailbox_capacity())
+    }
+
+    fn mailbox_kind(&self) -> MailboxKind<A::Msg> {
+        self.kind
+            .clone()
+            .unwrap_or_else(|| self.props.mailbox_kind())
+    }
+}
+
+impl<A: Actor> fmt::Display for ActorPropsWithMailbox<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Props")
+    }
+}
+
+impl<A: Actor> fmt::Debug for ActorPropsWithMailbox<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Props")
+    }
+}
+
 pub trait ActorArgs: Clone + Send + Sync + 'static {}
 impl<T: Clone + Send + Sync + 'static> ActorArgs for T {}
@@ -0,0 +1,537 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    fmt,
+    hash::{Hash, Hasher},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::{
+    actor::{
+        Actor, ActorRef, ActorRefFactory, ActorReference, ActorSelectionFactory, BasicActorRef,
+        BoxActorProd, Context, CreateError, DeadLetterReason, Props, Sender, SupervisorStrategy,
+    },
+    system::{SystemMsg, Timer},
+    util::random_u64,
+    Envelope, Message,
+};
+
+pub type RouterRef<Msg> = ActorRef<RouterMsg<Msg>>;
+
+pub type HashKeyFn<Msg> = Arc<dyn Fn(&Msg) -> u64 + Send + Sync>;
+
+/// How a router selects the routee that receives a message
+pub enum RoutingLogic<Msg> {
+    /// Each routee in turn
+    RoundRobin,
+
+    /// A randomly selected routee
+    Random,
+
+    /// The routee with the fewest queued messages, preferring one that is idle
+    SmallestMailbox,
+
+    /// All routees
+    Broadcast,
+
+    /// The routee owning the hash of the message on a hash ring, where each
+    /// routee has `virtual_nodes` points.
+    ///
+    /// Messages with the same hash are routed to the same routee, and adding
+    /// or removing a routee only remaps the hashes next to its points.
+    ConsistentHashing {
+        virtual_nodes: usize,
+        hash: HashKeyFn<Msg>,
+    },
+}
+
+impl<Msg: Message> RoutingLogic<Msg> {
+    /// Consistent hashing by the `ConsistentHashable::hash_key` of messages.
+    pub fn consistent_hashing(virtual_nodes: usize) -> Self
+    where
+        Msg: ConsistentHashable,
+    {
+        RoutingLogic::ConsistentHashing {
+            virtual_nodes,
+            hash: Arc::new(|msg: &Msg| hash(&msg.hash_key())),
+        }
+    }
+}
+
+impl<Msg> Clone for RoutingLogic<Msg> {
+    fn clone(&self) -> Self {
+        match self {
+            RoutingLogic::RoundRobin => RoutingLogic::RoundRobin,
+            RoutingLogic::Random => RoutingLogic::Random,
+            RoutingLogic::SmallestMailbox => RoutingLogic::SmallestMailbox,
+            RoutingLogic::Broadcast => RoutingLogic::Broadcast,
+            RoutingLogic::ConsistentHashing {
+                virtual_nodes,
+                hash,
+            } => RoutingLogic::ConsistentHashing {
+                virtual_nodes: *virtual_nodes,
+                hash: hash.clone(),
+            },
+        }
+    }
+}
+
+impl<Msg> fmt::Debug for RoutingLogic<Msg> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RoutingLogic::RoundRobin => f.write_str("RoundRobin"),
+            RoutingLogic::Random => f.write_str("Random"),
+            RoutingLogic::SmallestMailbox => f.write_str("SmallestMailbox"),
+            RoutingLogic::Broadcast => f.write_str("Broadcast"),
+            RoutingLogic::ConsistentHashing { virtual_nodes, .. } => {
+                write!(f, "ConsistentHashing({})", virtual_nodes)
+            }
+        }
+    }
+}
+
+/// Messages routed by a consistent-hashing router.
+///
+/// Typically implemented for an actor's message enum, returning for example
+/// the block hash or peer id that all messages about it share.
+pub trait ConsistentHashable {
+    type Key: Hash;
+
+    fn hash_key(&self) -> Self::Key;
+}
+
+fn hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Points of the routees on a hash ring, with the index of the routee at each point.
+///
+/// The points of a routee depend only on its path, so they stay
+/// the same when the ring is rebuilt after other routees change.
+struct HashRing {
+    points: BTreeMap<u64, usize>,
+}
+
+impl HashRing {
+    fn new(routees: &[BasicActorRef], virtual_nodes: usize) -> Self {
+        let mut points = BTreeMap::new();
+        for (i, routee) in routees.iter().enumerate() {
+            for node in 0..virtual_nodes {
+                points.insert(hash(&(routee.path().to_string(), node)), i);
+            }
+        }
+        HashRing { points }
+    }
+
+    /// Returns the routee at the first point from `hash`, wrapping around the ring.
+    fn get(&self, hash: u64) -> Option<usize> {
+        self.points
+            .range(hash..)
+            .next()
+            .or_else(|| self.points.iter().next())
+            .map(|(_, i)| *i)
+    }
+}
+
+/// Sends the wrapped message to all routees of a router, whatever its routing logic.
+#[derive(Clone, Debug)]
+pub struct Broadcast<Msg: Message>(pub Msg);
+
+/// Message of a router, created from the routed message or a `Broadcast`
+#[derive(Clone, Debug)]
+pub enum RouterMsg<Msg: Message> {
+    Route(Msg),
+    Broadcast(Msg),
+    #[doc(hidden)]
+    Resize,
+}
+
+impl<Msg: Message> From<Msg> for RouterMsg<Msg> {
+    fn from(msg: Msg) -> Self {
+        RouterMsg::Route(msg)
+    }
+}
+
+impl<Msg: Message> From<Broadcast<Msg>> for RouterMsg<Msg> {
+    fn from(msg: Broadcast<Msg>) -> Self {
+        RouterMsg::Broadcast(msg.0)
+    }
+}
+
+type SpawnFn<Msg> =
+    Arc<dyn Fn(&Context<RouterMsg<Msg>>, &str) -> Result<ActorRef<Msg>, CreateError> + Send + Sync>;
+
+#[derive(Clone)]
+enum Routees<Msg: Message> {
+    /// Routees created and supervised by the router
+    Pool { size: usize, spawn: SpawnFn<Msg> },
+
+    /// Paths of existing actors
+    Group(Vec<String>),
+}
+
+/// Configuration of a router, started with `props`.
+///
+/// # Examples
+///
+/// ```
+/// # use tezedge_actor_system::actors::*;
+///
+/// #[derive(Default)]
+/// struct Worker;
+///
+/// impl Actor for Worker {
+///     type Msg = u32;
+///
+///     fn recv(&mut self, _: &Context<u32>, job: u32, _: Sender) {
+///         println!("job {}", job);
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let backend = tokio::runtime::Handle::current().into();
+/// let sys = ActorSystem::new(backend).unwrap();
+///
+/// let props = RouterConfig::pool(4, Props::new::<Worker>(), RoutingLogic::RoundRobin).props();
+/// let workers = sys.actor_of_props("workers", props).unwrap();
+///
+/// workers.tell(1, None);
+/// workers.tell(Broadcast(2), None);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RouterConfig<Msg: Message> {
+    routees: Routees<Msg>,
+    logic: RoutingLogic<Msg>,
+    strategy: SupervisorStrategy,
+    resizer: Option<Resizer>,
+}
+
+impl<Msg: Message> RouterConfig<Msg> {
+    /// A router that creates `size` routees from `props` as its children.
+    ///
+    /// Failed routees are supervised by the router, restarting them by default.
+    pub fn pool<A>(size: usize, props: BoxActorProd<A>, logic: RoutingLogic<Msg>) -> Self
+    where
+        A: Actor<Msg = Msg>,
+    {
+        let spawn = move |ctx: &Context<RouterMsg<Msg>>, name: &str| {
+            ctx.actor_of_props(name, props.clone())
+        };
+
+        RouterConfig {
+            routees: Routees::Pool {
+                size,
+                spawn: Arc::new(spawn),
+            },
+            logic,
+            strategy: SupervisorStrategy::default(),
+            resizer: None,
+        }
+    }
+
+    /// A router to the existing actors at `paths`, such as `/user/worker`.
+    ///
+    /// The paths are resolved when the router starts, and routees are removed
+    /// when they terminate.
+    pub fn group<P: AsRef<str>>(paths: &[P], logic: RoutingLogic<Msg>) -> Self {
+        RouterConfig {
+            routees: Routees::Group(paths.iter().map(|p| p.as_ref().to_string()).collect()),
+            logic,
+            strategy: SupervisorStrategy::default(),
+            resizer: None,
+        }
+    }
+
+    /// Sets the strategy used to supervise the routees of a pool.
+    pub fn with_supervisor_strategy(self, strategy: SupervisorStrategy) -> Self {
+        RouterConfig { strategy, ..self }
+    }
+
+    /// Resizes a pool according to the load of its routees. Ignored by a group.
+    pub fn with_resizer(self, resizer: Resizer) -> Self {
+        RouterConfig {
+            resizer: Some(resizer),
+            ..self
+        }
+    }
+
+    /// Returns the `Props` of a router with this configuration.
+    pub fn props(self) -> BoxActorProd<Router<Msg>> {
+        Props::new_from_args(Router::new, self)
+    }
+}
+
+/// Grows and shrinks a router pool according to the load of its routees,
+/// set with `RouterConfig::with_resizer`.
+///
+/// Every `interval` the pool grows by `rampup_rate` of its size if all routees
+/// are under pressure, being busy with at least `pressure_threshold` more
+/// messages queued, and shrinks by `backoff_rate` of its size if less than
+/// `backoff_threshold` of the routees are busy. It changes by at least one
+/// routee, and its size is kept between `lower_bound` and `upper_bound`.
+///
+/// Removed routees no longer receive messages and are stopped
+/// once they have processed the messages already in their mailbox.
+#[derive(Clone, Debug)]
+pub struct Resizer {
+    pub lower_bound: usize,
+    pub upper_bound: usize,
+    pub pressure_threshold: usize,
+    pub rampup_rate: f64,
+    pub backoff_threshold: f64,
+    pub backoff_rate: f64,
+    pub interval: Duration,
+}
+
+impl Resizer {
+    /// A resizer keeping between `lower_bound` and `upper_bound` routees,
+    /// sampling their load every second.
+    pub fn new(lower_bound: usize, upper_bound: usize) -> Self {
+        Resizer {
+            lower_bound,
+            upper_bound,
+            pressure_threshold: 1,
+            rampup_rate: 0.2,
+            backoff_threshold: 0.3,
+            backoff_rate: 0.1,
+            interval: Duration::from_secs(1),
+        }
+    }
+
+    pub fn with_interval(self, interval: Duration) -> Self {
+        Resizer { interval, ..self }
+    }
+
+    /// Returns the number of routees to add, if positive, or to remove, if negative.
+    fn capacity_change(&self, routees: &[BasicActorRef]) -> isize {
+        let size = routees.len();
+        let busy = routees.iter().filter(|r| r.cell.is_busy()).count();
+        let pressured = routees
+            .iter()
+            .filter(|r| r.cell.is_busy() && r.cell.mailbox_len() >= self.pressure_threshold)
+            .count();
+
+        let change = if size > 0 && pressured == size {
+            ((size as f64 * self.rampup_rate).ceil() as isize).max(1)
+        } else if (busy as f64) < size as f64 * self.backoff_threshold {
+            -((size as f64 * self.backoff_rate).floor() as isize).max(1)
+        } else {
+            0
+        };
+
+        let target = (size as isize + change).clamp(
+            self.lower_bound as isize,
+            self.upper_bound.max(self.lower_bound) as isize,
+        );
+        target - size as isize
+    }
+}
+
+/// A specialized actor forwarding each message to one or more routees,
+/// selected by its `RoutingLogic`.
+///
+/// The original sender of a message is kept. Messages received while the
+/// router has no routees are published to dead letters.
+pub struct Router<Msg: Message> {
+    config: RouterConfig<Msg>,
+    routees: Vec<BasicActorRef>,
+    next: usize,
+    ring: Option<HashRing>,
+    /// Routees removed by the resizer, stopped once idle
+    retiring: Vec<BasicActorRef>,
+    spawned: usize,
+}
+
+impl<Msg: Message> Router<Msg> {
+    fn new(config: RouterConfig<Msg>) -> Self {
+        Router {
+            config,
+            routees: Vec::new(),
+            next: 0,
+            ring: None,
+            retiring: Vec::new(),
+            spawned: 0,
+        }
+    }
+
+    fn spawn_routee(&mut self, ctx: &Context<RouterMsg<Msg>>, spawn: &SpawnFn<Msg>) {
+        let name = format!("routee-{}", self.spawned);
+        self.spawned += 1;
+
+        match spawn(ctx, &name) {
+            Ok(routee) => self.add_routee(ctx, routee.into()),
+            Err(e) => slog::warn!(
+                ctx.system.log(),
+                "Router {} failed to create routee: {}",
+                ctx.myself.path(),
+                e
+            ),
+        }
+    }
+
+    fn add_routee(&mut self, ctx: &Context<RouterMsg<Msg>>, routee: BasicActorRef) {
+        ctx.watch(&routee);
+        self.routees.push(routee);
+        self.ring = None;
+    }
+
+    fn remove_routee(&mut self, routee: &BasicActorRef) {
+        self.routees.retain(|r| r != routee);
+        self.retiring.retain(|r| r != routee);
+        self.ring = None;
+    }
+
+    fn resize(&mut self, ctx: &Context<RouterMsg<Msg>>, resizer: &Resizer, spawn: &SpawnFn<Msg>) {
+        // stopping only idle routees loses none of their messages
+        for routee in &self.retiring {
+            if !routee.cell.is_busy() && routee.cell.mailbox_len() == 0 {
+                ctx.stop(routee.clone());
+            }
+        }
+
+        let change = resizer.capacity_change(&self.routees);
+        for _ in 0..change {
+            self.spawn_routee(ctx, spawn);
+        }
+        for _ in change..0 {
+            if let Some(routee) = self.routees.pop() {
+                self.retiring.push(routee);
+                self.ring = None;
+            }
+        }
+
+        ctx.schedule_once(resizer.interval, ctx.myself(), None, RouterMsg::Resize)
+            .detach();
+    }
+
+    /// Returns the indexes of the routees selected for a routed message.
+    fn select(&mut self, msg: &Msg) -> Vec<usize> {
+        let len = self.routees.len();
+        match &self.config.logic {
+            RoutingLogic::RoundRobin => {
+                let i = self.next % len;
+                self.next = i + 1;
+                vec![i]
+            }
+            RoutingLogic::Random => vec![(random_u64() % len as u64) as usize],
+            RoutingLogic::SmallestMailbox => {
+                let load = |r: &BasicActorRef| r.cell.mailbox_len() + r.cell.is_busy() as usize;
+                let smallest = (0..len).min_by_key(|i| load(&self.routees[*i]));
+                smallest.into_iter().collect()
+            }
+            RoutingLogic::Broadcast => (0..len).collect(),
+            RoutingLogic::ConsistentHashing {
+                virtual_nodes,
+                hash,
+            } => {
+                let routees = &self.routees;
+                let ring = self
+                    .ring
+                    .get_or_insert_with(|| HashRing::new(routees, *virtual_nodes));
+                ring.get(hash(msg)).into_iter().collect()
+            }
+        }
+    }
+
+    fn route(&mut self, ctx: &Context<RouterMsg<Msg>>, msg: Msg, sender: Sender, all: bool) {
+        if self.routees.is_empty() {
+            let msg = Envelope {
+                msg: format!("{:?}", msg),
+                sender,
+                timer: None,
+            };
+            let myself: BasicActorRef = ctx.myself().into();
+            myself.cell.dead_letter(msg, DeadLetterReason::NoRoutees);
+            return;
+        }
+
+        let selected = if all {
+            (0..self.routees.len()).collect()
+        } else {
+            self.select(&msg)
+        };
+
+        for i in selected {
+            let routee = &self.routees[i];
+            // a message that can't be delivered is published to dead letters
+            match routee.downcast::<Msg>() {
+                Some(routee) => {
+                    let _ = routee.try_tell(msg.clone(), sender.clone());
+                }
+                None => slog::warn!(
+                    ctx.system.log(),
+                    "Router {} dropped a message: routee {} does not receive its type",
+                    ctx.myself.path(),
+                    routee.path()
+                ),
+            }
+        }
+    }
+}
+
+impl<Msg: Message> Actor for Router<Msg> {
+    type Msg = RouterMsg<Msg>;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        match self.config.routees.clone() {
+            Routees::Pool { size, spawn } => {
+                for _ in 0..size {
+                    self.spawn_routee(ctx, &spawn);
+                }
+
+                if let Some(resizer) = &self.config.resizer {
+                    ctx.schedule_once(resizer.interval, ctx.myself(), None, RouterMsg::Resize)
+                        .detach();
+                }
+            }
+            Routees::Group(paths) => {
+                for path in paths {
+                    let routees = ctx
+                        .system
+                        .select(&path)
+                        .map(|selection| selection.resolve())
+                        .unwrap_or_default();
+                    if routees.is_empty() {
+                        slog::warn!(
+                            ctx.system.log(),
+                            "Router {} found no actor at {}",
+                            ctx.myself.path(),
+                            path
+                        );
+                    }
+                    for routee in routees {
+                        self.add_routee(ctx, routee);
+                    }
+                }
+            }
+        }
+    }
+
+    fn sys_recv(&mut self, _: &Context<Self::Msg>, msg: SystemMsg, _: Sender) {
+        if let SystemMsg::Terminated(terminated) = msg {
+            self.remove_routee(&terminated);
+        }
+    }
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
+        match msg {
+            RouterMsg::Route(msg) => self.route(ctx, msg, sender, false),
+            RouterMsg::Broadcast(msg) => self.route(ctx, msg, sender, true),
+            RouterMsg::Resize => {
+                if let (Routees::Pool { spawn, .. }, Some(resizer)) =
+                    (self.config.routees.clone(), self.config.resizer.clone())
+                {
+                    self.resize(ctx, &resizer, &spawn);
+                }
+            }
+        }
+    }
+
+    fn supervisor_strategy(&self) -> SupervisorStrategy {
+        self.config.strategy.clone()
+    }
+}
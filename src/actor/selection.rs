@@ -0,0 +1,180 @@
+use crate::{
+    actor::{ActorRef, ActorReference, BasicActorRef, Sender},
+    system::SystemMsg,
+    validate::{validate_path, InvalidPath},
+    Message,
+};
+
+/// A selection of actors by path, such as `/user/shell/*` or `../sibling`.
+///
+/// The path is resolved against the actor tree each time the selection
+/// is used, so actors started or stopped in the meantime are taken into account.
+///
+/// A path starting with `/` is absolute. Other paths are relative to the actor
+/// that made the selection, or to the user root when selecting from the system.
+/// A segment can be `..` for the parent, `.` for the current actor,
+/// or a name where `*` matches any characters and `?` matches one character.
+///
+/// # Examples
+///
+/// ```
+/// # use tezedge_actor_system::actors::*;
+///
+/// #[derive(Default)]
+/// struct Worker;
+///
+/// impl Actor for Worker {
+///     type Msg = String;
+///
+///     fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, _sender: Sender) {
+///         println!("{} received {}", ctx.myself.name(), msg);
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let backend = tokio::runtime::Handle::current().into();
+/// let sys = ActorSystem::new(backend).unwrap();
+///
+/// sys.actor_of::<Worker>("worker-1").unwrap();
+/// sys.actor_of::<Worker>("worker-2").unwrap();
+///
+/// let selection = sys.select("/user/worker-*").unwrap();
+/// selection.try_tell("hello".to_string(), None);
+///
+/// let worker: Option<ActorRef<String>> = sys.lookup("worker-1");
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ActorSelection {
+    anchor: BasicActorRef,
+    path: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Clone)]
+enum Segment {
+    Parent,
+    Name(String),
+    Pattern(String),
+}
+
+impl ActorSelection {
+    /// Selects the actors at `path`, resolving a relative path from `anchor`.
+    pub fn new(anchor: BasicActorRef, path: &str) -> Result<ActorSelection, InvalidPath> {
+        validate_path(path)?;
+
+        let mut anchor = anchor;
+        if path.starts_with('/') {
+            while !anchor.is_root() {
+                anchor = anchor.parent();
+            }
+        }
+
+        let segments = path
+            .split('/')
+            .filter_map(|segment| match segment {
+                "" | "." => None,
+                ".." => Some(Segment::Parent),
+                s if s.contains(['*', '?']) => Some(Segment::Pattern(s.to_string())),
+                s => Some(Segment::Name(s.to_string())),
+            })
+            .collect();
+
+        Ok(ActorSelection {
+            anchor,
+            path: path.to_string(),
+            segments,
+        })
+    }
+
+    /// The path the selection was made with.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The actors currently matching the selection.
+    pub fn resolve(&self) -> Vec<BasicActorRef> {
+        let mut actors = vec![self.anchor.clone()];
+
+        for segment in &self.segments {
+            let mut next: Vec<BasicActorRef> = Vec::new();
+            for actor in &actors {
+                match segment {
+                    Segment::Parent if !actor.is_root() => next.push(actor.parent()),
+                    Segment::Parent => {}
+                    Segment::Name(name) => next.extend(actor.children().find(|c| c.name() == name)),
+                    Segment::Pattern(pattern) => next.extend(
+                        actor
+                            .children()
+                            .filter(|c| matches_pattern(pattern.as_bytes(), c.name().as_bytes())),
+                    ),
+                }
+            }
+
+            // siblings are kept together, so `..` repeats the same parent consecutively
+            next.dedup_by(|a, b| a.path() == b.path());
+            actors = next;
+        }
+
+        actors
+    }
+
+    /// Sends a clone of `msg` to each selected actor.
+    ///
+    /// Returns the number of actors that accepted the message.
+    /// Actors that don't receive messages of type `Msg` are skipped.
+    pub fn try_tell<Msg>(&self, msg: Msg, sender: impl Into<Option<BasicActorRef>>) -> usize
+    where
+        Msg: Message,
+    {
+        let sender: Sender = sender.into();
+        self.resolve()
+            .iter()
+            .filter(|actor| actor.try_tell(msg.clone(), sender.clone()).is_ok())
+            .count()
+    }
+
+    /// Sends a system message to each selected actor.
+    pub fn sys_tell(&self, msg: SystemMsg) {
+        for actor in self.resolve() {
+            actor.sys_tell(msg.clone());
+        }
+    }
+
+    /// The first selected actor with message type `Msg`.
+    pub fn lookup<Msg: Message>(&self) -> Option<ActorRef<Msg>> {
+        self.resolve().iter().find_map(BasicActorRef::downcast)
+    }
+}
+
+/// Produces actor selections from paths relative to a position in the actor tree.
+pub trait ActorSelectionFactory {
+    /// Selects the actors at `path`.
+    ///
+    /// Fails if the path is not a valid selection path.
+    fn select(&self, path: &str) -> Result<ActorSelection, InvalidPath>;
+
+    /// Looks up an actor at `path` with message type `Msg`.
+    ///
+    /// Returns `None` if no actor matches the path,
+    /// or if the matching actors have a different message type.
+    fn lookup<Msg: Message>(&self, path: &str) -> Option<ActorRef<Msg>> {
+        self.select(path).ok()?.lookup()
+    }
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any characters
+/// and `?` a single character.
+fn matches_pattern(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.split_first(), name.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            matches_pattern(rest, name)
+                || (!name.is_empty() && matches_pattern(pattern, &name[1..]))
+        }
+        (Some((b'?', rest)), Some((_, name))) => matches_pattern(rest, name),
+        (Some((p, rest)), Some((n, name))) if p == n => matches_pattern(rest, name),
+        _ => false,
+    }
+}
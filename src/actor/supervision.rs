@@ -0,0 +1,192 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use crate::util::random_u64;
+
+/// The action a supervisor takes when one of its children fails.
+///
+/// A child fails when it panics while handling a message.
+/// The message that caused the failure is not delivered again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Replace the failed actor with a new instance produced by its `Props`
+    Restart,
+
+    /// Keep the failed actor instance and continue with the next message
+    Resume,
+
+    /// Stop the failed actor
+    Stop,
+
+    /// Fail the supervisor itself, leaving the decision to its own parent
+    Escalate,
+}
+
+/// The children a `Strategy` is applied to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SupervisorScope {
+    /// Only the failed child
+    OneForOne,
+
+    /// The failed child and all of its siblings
+    AllForOne,
+}
+
+/// Limits how often a child can be restarted.
+///
+/// Once a child has been restarted `max_restarts` times within the
+/// `within` duration, the `exceeded` strategy is applied instead of
+/// restarting it again. An `exceeded` of `Restart` is treated as `Stop`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RestartLimit {
+    pub max_restarts: u32,
+    pub within: Duration,
+    pub exceeded: Strategy,
+}
+
+/// Exponential backoff between consecutive restarts of a child.
+///
+/// The n-th consecutive restart is delayed by `min * 2^(n-1)`, capped at `max`,
+/// and then extended by a random amount of up to `random_factor` times the delay.
+/// The count is reset once a child has not failed for `max` after its last restart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backoff {
+    pub min: Duration,
+    pub max: Duration,
+    pub random_factor: f64,
+}
+
+impl Backoff {
+    fn delay(&self, consecutive: u32) -> Duration {
+        let delay = self
+            .min
+            .checked_mul(1u32 << consecutive.min(31))
+            .unwrap_or(self.max)
+            .min(self.max);
+
+        // 53 random bits give a uniformly distributed f64 in [0, 1)
+        let random = (random_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        delay.mul_f64(1.0 + random * self.random_factor.max(0.0))
+    }
+}
+
+/// Describes how a supervisor handles failed children.
+///
+/// Returned by `Actor::supervisor_strategy`. The default restarts
+/// only the failed child, immediately and without limit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SupervisorStrategy {
+    pub strategy: Strategy,
+    pub scope: SupervisorScope,
+    pub restart_limit: Option<RestartLimit>,
+    pub backoff: Option<Backoff>,
+}
+
+impl SupervisorStrategy {
+    /// Applies `strategy` to the failed child only.
+    pub fn one_for_one(strategy: Strategy) -> Self {
+        SupervisorStrategy {
+            strategy,
+            scope: SupervisorScope::OneForOne,
+            restart_limit: None,
+            backoff: None,
+        }
+    }
+
+    /// Applies `strategy` to the failed child and all of its siblings.
+    pub fn all_for_one(strategy: Strategy) -> Self {
+        SupervisorStrategy {
+            strategy,
+            scope: SupervisorScope::AllForOne,
+            restart_limit: None,
+            backoff: None,
+        }
+    }
+
+    /// Applies `exceeded` instead of restarting a child that was already
+    /// restarted `max_restarts` times within `within`.
+    pub fn with_restart_limit(
+        self,
+        max_restarts: u32,
+        within: Duration,
+        exceeded: Strategy,
+    ) -> Self {
+        SupervisorStrategy {
+            restart_limit: Some(RestartLimit {
+                max_restarts,
+                within,
+                exceeded,
+            }),
+            ..self
+        }
+    }
+
+    /// Delays consecutive restarts of a child with an exponential backoff.
+    pub fn with_backoff(self, min: Duration, max: Duration, random_factor: f64) -> Self {
+        SupervisorStrategy {
+            backoff: Some(Backoff {
+                min,
+                max,
+                random_factor,
+            }),
+            ..self
+        }
+    }
+}
+
+impl Default for SupervisorStrategy {
+    fn default() -> Self {
+        SupervisorStrategy::one_for_one(Strategy::Restart)
+    }
+}
+
+/// Restart history of a single child, kept by its supervisor.
+#[derive(Default)]
+pub(crate) struct RestartStats {
+    history: VecDeque<Instant>,
+    consecutive: u32,
+    last_due: Option<Instant>,
+}
+
+impl RestartStats {
+    /// Records a restart of the child.
+    ///
+    /// Returns the delay to wait before restarting,
+    /// or `None` if the restart limit is exceeded.
+    pub(crate) fn restart(
+        &mut self,
+        strategy: &SupervisorStrategy,
+        now: Instant,
+    ) -> Option<Duration> {
+        if let Some(limit) = strategy.restart_limit.as_ref() {
+            while let Some(at) = self.history.front() {
+                if now.duration_since(*at) > limit.within {
+                    self.history.pop_front();
+                } else {
+                    break;
+                }
+            }
+
+            if self.history.len() >= limit.max_restarts as usize {
+                return None;
+            }
+            self.history.push_back(now);
+        }
+
+        let backoff = match strategy.backoff.as_ref() {
+            Some(backoff) => backoff,
+            None => return Some(Duration::default()),
+        };
+
+        match self.last_due {
+            Some(due) if now <= due + backoff.max => self.consecutive += 1,
+            _ => self.consecutive = 0,
+        }
+
+        let delay = backoff.delay(self.consecutive);
+        self.last_due = Some(now + delay);
+        Some(delay)
+    }
+}
@@ -1,15 +1,136 @@
 use std::{
     fmt,
     hash::{Hash, Hasher},
+    str::FromStr,
     sync::Arc,
 };
 
+use crate::validate::{validate_name, InvalidName, InvalidPath};
+
+/// The absolute path of an actor in the actor tree, e.g. `/user/actor_a/actor_b`.
+///
+/// Parsing a path with `FromStr` validates each name in it.
 pub struct ActorPath(Arc<str>);
 
 impl ActorPath {
+    /// Creates a path from `path` without validating it.
     pub fn new(path: &str) -> Self {
         ActorPath(Arc::from(path))
     }
+
+    /// The path of the root actor, `/`.
+    pub fn root() -> Self {
+        ActorPath::new("/")
+    }
+
+    pub fn is_root(&self) -> bool {
+        &*self.0 == "/"
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The names along the path, starting below the root.
+    pub fn segments(&self) -> impl Iterator<Item = &str> {
+        self.0.split('/').filter(|segment| !segment.is_empty())
+    }
+
+    /// The last name of the path, or an empty string for the root.
+    pub fn name(&self) -> &str {
+        self.segments().last().unwrap_or("")
+    }
+
+    /// The number of names in the path, `0` for the root.
+    pub fn depth(&self) -> usize {
+        self.segments().count()
+    }
+
+    /// The path of the parent, or `None` for the root.
+    pub fn parent(&self) -> Option<ActorPath> {
+        match self.0.rfind('/')? {
+            _ if self.is_root() => None,
+            0 => Some(ActorPath::root()),
+            end => Some(ActorPath::new(&self.0[..end])),
+        }
+    }
+
+    /// The path of a child named `name`.
+    ///
+    /// Fails if `name` is not a valid actor name.
+    pub fn child(&self, name: &str) -> Result<ActorPath, InvalidName> {
+        validate_name(name)?;
+
+        if self.is_root() {
+            Ok(ActorPath::new(&format!("/{}", name)))
+        } else {
+            Ok(ActorPath::new(&format!("{}/{}", self.0, name)))
+        }
+    }
+
+    /// Whether this path is below `ancestor`, excluding `ancestor` itself.
+    pub fn is_descendant_of(&self, ancestor: &ActorPath) -> bool {
+        if ancestor.is_root() {
+            return !self.is_root();
+        }
+
+        self.0
+            .strip_prefix(&*ancestor.0)
+            .is_some_and(|rest| rest.starts_with('/'))
+    }
+
+    /// Resolves `path` relative to this path.
+    ///
+    /// `..` moves to the parent and `.` stays on the current path.
+    /// A `path` starting with `/` is resolved from the root.
+    /// Moving above the root stays on the root.
+    pub fn resolve(&self, path: &str) -> Result<ActorPath, InvalidPath> {
+        let mut resolved = if path.starts_with('/') {
+            ActorPath::root()
+        } else {
+            self.clone()
+        };
+
+        for segment in path.split('/') {
+            match segment {
+                "" | "." => {}
+                ".." => {
+                    if let Some(parent) = resolved.parent() {
+                        resolved = parent;
+                    }
+                }
+                name => {
+                    resolved = resolved
+                        .child(name)
+                        .map_err(|_| InvalidPath::actor_path(path))?;
+                }
+            }
+        }
+
+        Ok(resolved)
+    }
+}
+
+impl FromStr for ActorPath {
+    type Err = InvalidPath;
+
+    /// Parses an absolute path, such as `/user/actor_a`.
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidPath::actor_path(path);
+
+        let names = path.strip_prefix('/').ok_or_else(invalid)?;
+        if names.is_empty() {
+            return Ok(ActorPath::root());
+        }
+
+        for name in names.split('/') {
+            if name.is_empty() || validate_name(name).is_err() {
+                return Err(invalid());
+            }
+        }
+
+        Ok(ActorPath::new(path))
+    }
 }
 
 impl PartialEq for ActorPath {
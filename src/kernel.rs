@@ -10,7 +10,6 @@ pub enum KernelMsg {
     TerminateActor,
     RestartActor,
     RunActor,
-    Sys,
 }
 use std::{
     panic::{catch_unwind, AssertUnwindSafe},
@@ -54,7 +53,7 @@ where
     let (tx, rx) = sys.backend.channel(1000); // todo config?
     let kr = KernelRef { tx: Arc::new(tx) };
 
-    let mut asys = sys.clone();
+    let asys = sys.clone();
     let akr = kr.clone();
     let actor = start_actor(&props)?;
     let cell = cell.init(&kr);
@@ -83,12 +82,10 @@ where
                 restart_actor(&dock, actor_ref.clone().into(), &props, &asys);
             }
             KernelMsg::TerminateActor => {
+                actor_ref.cell.clear_stash(DeadLetterReason::Terminated);
                 terminate_actor(&mailbox, actor_ref.clone().into(), &asys);
                 return false;
             }
-            KernelMsg::Sys => {
-                asys.complete_start();
-            }
         }
         true
     };
@@ -108,6 +105,10 @@ fn restart_actor<A>(
     let mut a = dock.actor.lock().unwrap();
     match start_actor(props) {
         Ok(actor) => {
+            dock.cell.reset_behaviors();
+            if !actor.keep_stash_on_restart() {
+                dock.cell.clear_stash(DeadLetterReason::Restarted);
+            }
             *a = Some(actor);
             actor_ref.sys_tell(SystemMsg::ActorInit);
             sys.publish_event(ActorRestarted { actor: actor_ref }.into());
@@ -124,6 +125,8 @@ where
 {
     sys.provider.unregister(actor_ref.path());
     flush_to_deadletters(mbox, &actor_ref, sys);
+    actor_ref.cell.notify_watchers();
+    actor_ref.cell.cancel_pipes();
     sys.publish_event(
         ActorTerminated {
             actor: actor_ref.clone(),
@@ -131,9 +134,11 @@ where
         .into(),
     );
 
-    let parent = actor_ref.parent();
-    if !parent.is_root() {
-        parent.sys_tell(ActorTerminated { actor: actor_ref }.into());
+    // the parent of the root has no kernel
+    if !actor_ref.is_root() {
+        actor_ref
+            .parent()
+            .sys_tell(ActorTerminated { actor: actor_ref }.into());
     }
 }
 
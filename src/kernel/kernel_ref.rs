@@ -3,10 +3,10 @@ use std::sync::Arc;
 use crate::{
     actor::{MsgError, MsgResult},
     kernel::{
-        mailbox::{AnyEnqueueError, AnySender, MailboxSchedule, MailboxSender},
+        mailbox::{AnyEnqueueResult, AnySender, MailboxSchedule, MailboxSender},
         KernelMsg,
     },
-    system::SendingBackend,
+    system::{ScheduleId, SendingBackend},
     AnyMessage, Envelope, Message,
 };
 
@@ -28,46 +28,66 @@ impl KernelRef {
         self.send(KernelMsg::TerminateActor);
     }
 
-    pub(crate) fn sys_init(&self) {
-        self.send(KernelMsg::Sys);
-    }
-
     fn send(&self, msg: KernelMsg) {
         self.tx.send_msg(msg)
     }
 }
 
+/// `Ok(Some(dropped))` when a message was accepted but `dropped`
+/// was discarded because the mailbox is full.
+pub type DispatchResult<Msg> = Result<Option<Envelope<Msg>>, MsgError<Envelope<Msg>>>;
+
 pub fn dispatch<Msg>(
     msg: Envelope<Msg>,
     mbox: &MailboxSender<Msg>,
     kernel: &KernelRef,
+) -> DispatchResult<Msg>
+where
+    Msg: Message,
+{
+    let dropped = mbox.try_enqueue(msg).map_err(|e| MsgError {
+        msg: e.msg,
+        reason: e.reason,
+    })?;
+    schedule(mbox, kernel);
+    Ok(dropped)
+}
+
+/// Like `dispatch`, but waits for space if the mailbox is full.
+pub async fn dispatch_wait<Msg>(
+    msg: Envelope<Msg>,
+    mbox: &MailboxSender<Msg>,
+    kernel: &KernelRef,
 ) -> MsgResult<Envelope<Msg>>
 where
     Msg: Message,
 {
-    match mbox.try_enqueue(msg) {
-        Ok(_) => {
-            if !mbox.is_scheduled() {
-                mbox.set_scheduled(true);
-                kernel.schedule();
-            }
+    mbox.enqueue(msg).await.map_err(|e| MsgError {
+        msg: e.msg,
+        reason: e.reason,
+    })?;
+    schedule(mbox, kernel);
+    Ok(())
+}
 
-            Ok(())
-        }
-        Err(e) => Err(MsgError::new(e.msg)),
+fn schedule<Msg: Message>(mbox: &MailboxSender<Msg>, kernel: &KernelRef) {
+    if !mbox.is_scheduled() {
+        mbox.set_scheduled(true);
+        kernel.schedule();
     }
 }
 
 pub fn dispatch_any(
     msg: &mut AnyMessage,
     sender: crate::actor::Sender,
+    timer: Option<ScheduleId>,
     mbox: &Arc<dyn AnySender>,
     kernel: &KernelRef,
-) -> Result<(), AnyEnqueueError> {
-    mbox.try_any_enqueue(msg, sender).map(|_| {
-        if !mbox.is_sched() {
-            mbox.set_sched(true);
-            kernel.schedule();
-        }
-    })
+) -> AnyEnqueueResult {
+    let dropped = mbox.try_any_enqueue(msg, sender, timer)?;
+    if !mbox.is_sched() {
+        mbox.set_sched(true);
+        kernel.schedule();
+    }
+    Ok(dropped)
 }
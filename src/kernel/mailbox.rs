@@ -1,3 +1,6 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::panic::{catch_unwind, resume_unwind, AssertUnwindSafe};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
@@ -5,14 +8,16 @@ use std::sync::{
 use std::thread;
 
 use crate::{
-    actor::actor_cell::ExtendedCell,
     actor::*,
+    actor::{actor_cell::ExtendedCell, async_actor::PendingRecv, channel::is_dead_letter},
     kernel::{
-        queue::{queue, EnqueueResult, QueueEmpty, QueueReader, QueueWriter},
+        queue::{
+            queue, queue_with, EnqueueError, EnqueueResult, QueueEmpty, QueueReader, QueueWriter,
+        },
         Dock,
     },
     system::ActorCreated,
-    system::{ActorSystem, SystemEvent, SystemMsg},
+    system::{ActorSystem, ScheduleId, SystemCmd, SystemEvent, SystemMsg},
     AnyMessage, Envelope, Message,
 };
 
@@ -23,25 +28,41 @@ pub trait MailboxSchedule {
 }
 
 #[derive(Debug)]
-pub struct AnyEnqueueError;
+pub struct AnyEnqueueError {
+    /// The message rejected by a full mailbox, formatted for dead letters
+    pub(crate) rejected: Option<Envelope<String>>,
+}
 
 impl From<()> for AnyEnqueueError {
     fn from(_: ()) -> AnyEnqueueError {
-        AnyEnqueueError
+        AnyEnqueueError { rejected: None }
     }
 }
 
+/// `Ok(Some(dropped))` when a message was accepted but `dropped`, formatted
+/// for dead letters, was discarded because the mailbox is full.
+pub type AnyEnqueueResult = Result<Option<Envelope<String>>, AnyEnqueueError>;
+
 pub trait AnySender: Send + Sync {
-    fn try_any_enqueue(&self, msg: &mut AnyMessage, sender: Sender) -> Result<(), AnyEnqueueError>;
+    fn try_any_enqueue(
+        &self,
+        msg: &mut AnyMessage,
+        sender: Sender,
+        timer: Option<ScheduleId>,
+    ) -> AnyEnqueueResult;
 
     fn set_sched(&self, b: bool);
 
     fn is_sched(&self) -> bool;
+
+    /// Number of messages waiting in the mailbox
+    fn queue_len(&self) -> usize;
 }
 
 #[derive(Clone)]
 pub struct MailboxSender<Msg: Message> {
     queue: QueueWriter<Msg>,
+    overflow: OverflowPolicy,
     scheduled: Arc<AtomicBool>,
 }
 
@@ -49,8 +70,29 @@ impl<Msg> MailboxSender<Msg>
 where
     Msg: Message,
 {
+    /// Adds `msg` to the mailbox, applying the overflow policy if the mailbox is full.
     pub fn try_enqueue(&self, msg: Envelope<Msg>) -> EnqueueResult<Msg> {
-        self.queue.try_enqueue(msg)
+        match self.overflow {
+            OverflowPolicy::DropNewest => match self.queue.try_enqueue(msg) {
+                Err(e) if e.reason == DeadLetterReason::MailboxFull => Ok(Some(e.msg)),
+                result => result,
+            },
+            OverflowPolicy::DropOldest => self.queue.enqueue_evicting(msg),
+            OverflowPolicy::Reject => self.queue.try_enqueue(msg),
+        }
+    }
+
+    /// Adds `msgs` to the head of the mailbox, keeping their order.
+    pub fn enqueue_front(
+        &self,
+        msgs: Vec<Envelope<Msg>>,
+    ) -> Result<(), EnqueueError<Vec<Envelope<Msg>>>> {
+        self.queue.enqueue_front(msgs)
+    }
+
+    /// Adds `msg` to the mailbox, waiting for space if the mailbox is full.
+    pub async fn enqueue(&self, msg: Envelope<Msg>) -> EnqueueResult<Msg> {
+        self.queue.enqueue(msg).await
     }
 }
 
@@ -71,13 +113,32 @@ impl<Msg> AnySender for MailboxSender<Msg>
 where
     Msg: Message,
 {
-    fn try_any_enqueue(&self, msg: &mut AnyMessage, sender: Sender) -> Result<(), AnyEnqueueError> {
-        let actual = msg.take().map_err(|_| AnyEnqueueError)?;
+    fn try_any_enqueue(
+        &self,
+        msg: &mut AnyMessage,
+        sender: Sender,
+        timer: Option<ScheduleId>,
+    ) -> AnyEnqueueResult {
+        let actual = msg.take().map_err(|_| AnyEnqueueError::from(()))?;
         let msg = Envelope {
             msg: actual,
             sender,
+            timer,
+        };
+
+        let format = |e: Envelope<Msg>| Envelope {
+            msg: format!("{:?}", e.msg),
+            sender: e.sender,
+            timer: e.timer,
         };
-        self.try_enqueue(msg).map_err(|_| AnyEnqueueError)
+
+        match self.try_enqueue(msg) {
+            Ok(dropped) => Ok(dropped.map(format)),
+            Err(e) if e.reason == DeadLetterReason::MailboxFull => Err(AnyEnqueueError {
+                rejected: Some(format(e.msg)),
+            }),
+            Err(_) => Err(AnyEnqueueError::from(())),
+        }
     }
 
     fn set_sched(&self, b: bool) {
@@ -87,6 +148,10 @@ where
     fn is_sched(&self) -> bool {
         self.is_scheduled()
     }
+
+    fn queue_len(&self) -> usize {
+        self.queue.len()
+    }
 }
 
 #[derive(Clone)]
@@ -147,22 +212,31 @@ where
 
 pub fn mailbox<Msg>(
     msg_process_limit: u32,
+    capacity: MailboxCapacity,
+    kind: MailboxKind<Msg>,
 ) -> (MailboxSender<Msg>, MailboxSender<SystemMsg>, Mailbox<Msg>)
 where
     Msg: Message,
 {
-    let (qw, qr) = queue::<Msg>();
+    let (capacity, overflow) = match capacity {
+        MailboxCapacity::Unbounded => (None, OverflowPolicy::Reject),
+        MailboxCapacity::Bounded { capacity, overflow } => (Some(capacity), overflow),
+    };
+    let (qw, qr) = queue_with::<Msg>(capacity, kind);
+    // system messages are never dropped
     let (sqw, sqr) = queue::<SystemMsg>();
 
     let scheduled = Arc::new(AtomicBool::new(false));
 
     let sender = MailboxSender {
         queue: qw,
+        overflow,
         scheduled: scheduled.clone(),
     };
 
     let sys_sender = MailboxSender {
         queue: sqw,
+        overflow: OverflowPolicy::Reject,
         scheduled: scheduled.clone(),
     };
 
@@ -194,22 +268,31 @@ where
     let mut actor = dock.actor.lock().unwrap().take();
     let cell = &mut dock.cell;
 
-    process_sys_msgs(sen.mbox, &ctx, cell, &mut actor);
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        process_sys_msgs(sen.mbox, &ctx, cell, &mut actor);
 
-    if actor.is_some() && !sen.mbox.is_suspended() {
-        process_msgs(sen.mbox, &ctx, cell, &mut actor);
-    }
+        if actor.is_some() && !sen.mbox.is_suspended() {
+            process_msgs(sen.mbox, &ctx, cell, &mut actor);
+        }
 
-    process_sys_msgs(sen.mbox, &ctx, cell, &mut actor);
+        process_sys_msgs(sen.mbox, &ctx, cell, &mut actor);
+    }));
 
+    // Keep the actor instance even if it panicked,
+    // since the supervisor may decide to resume it
     if actor.is_some() {
         let mut a = dock.actor.lock().unwrap();
         *a = actor;
     }
 
+    if let Err(panic) = result {
+        resume_unwind(panic);
+    }
+
     sen.mbox.set_scheduled(false);
 
-    let has_msgs = sen.mbox.has_msgs() || sen.mbox.has_sys_msgs();
+    // messages of a suspended mailbox wait until it is resumed
+    let has_msgs = (sen.mbox.has_msgs() && !sen.mbox.is_suspended()) || sen.mbox.has_sys_msgs();
     if has_msgs && !sen.mbox.is_scheduled() {
         ctx.kernel.schedule();
     }
@@ -229,8 +312,19 @@ fn process_msgs<A>(
         if count < mbox.msg_process_limit() {
             match mbox.try_dequeue() {
                 Ok(msg) => {
+                    // a timer cancelled or replaced since it sent the message
+                    if let Some(id) = &msg.timer {
+                        if !cell.dequeue_timer_msg(id) {
+                            continue;
+                        }
+                    }
+
                     let (msg, sender) = (msg.msg, msg.sender);
-                    actor.as_mut().unwrap().recv(ctx, msg, sender);
+                    cell.receive(actor.as_mut().unwrap(), ctx, msg, sender);
+                    if let Some(pending) = cell.take_pending() {
+                        await_pending(pending, mbox, ctx);
+                        break;
+                    }
                     process_sys_msgs(mbox, ctx, cell, actor);
 
                     count += 1;
@@ -245,6 +339,29 @@ fn process_msgs<A>(
     }
 }
 
+/// Suspends the mailbox until the future of the message being received has completed.
+fn await_pending<Msg>(pending: PendingRecv, mbox: &Mailbox<Msg>, ctx: &Context<Msg>)
+where
+    Msg: Message,
+{
+    mbox.set_suspended(true);
+
+    let mbox = mbox.clone();
+    let actor: BasicActorRef = ctx.myself().into();
+    let kernel = ctx.kernel.clone();
+
+    ctx.system.backend.spawn(async move {
+        match pending.await {
+            Ok(()) => {
+                mbox.set_suspended(false);
+                kernel.schedule();
+            }
+            // the mailbox stays suspended, as when `Sentinel` reports a panic
+            Err(_) => actor.parent().sys_tell(SystemMsg::Failed(actor.clone())),
+        }
+    });
+}
+
 fn process_sys_msgs<A>(
     mbox: &Mailbox<A::Msg>,
     ctx: &Context<A::Msg>,
@@ -265,9 +382,11 @@ fn process_sys_msgs<A>(
     for msg in sys_msgs {
         match msg.msg {
             SystemMsg::ActorInit => handle_init(mbox, ctx, cell, actor),
+            SystemMsg::Command(SystemCmd::Resume) => handle_resume(mbox, cell, actor),
             SystemMsg::Command(cmd) => cell.receive_cmd(cmd, actor),
             SystemMsg::Event(evt) => handle_evt(evt, ctx, cell, actor),
-            SystemMsg::Failed(failed) => handle_failed(failed, cell),
+            SystemMsg::Failed(failed) => handle_failed(failed, mbox, cell, actor),
+            SystemMsg::Terminated(terminated) => handle_terminated(terminated, ctx, actor),
         }
     }
 }
@@ -295,11 +414,33 @@ fn handle_init<A>(
     actor.as_mut().unwrap().post_start(ctx);
 }
 
-fn handle_failed<Msg>(failed: BasicActorRef, cell: &ExtendedCell<Msg>)
+fn handle_resume<A>(mbox: &Mailbox<A::Msg>, cell: &ExtendedCell<A::Msg>, actor: &mut Option<A>)
 where
-    Msg: Message,
+    A: Actor,
+{
+    mbox.set_suspended(false);
+    cell.receive_cmd(SystemCmd::Resume, actor);
+}
+
+fn handle_failed<A>(
+    failed: BasicActorRef,
+    mbox: &Mailbox<A::Msg>,
+    cell: &ExtendedCell<A::Msg>,
+    actor: &mut Option<A>,
+) where
+    A: Actor,
 {
-    cell.handle_failure(failed)
+    let strategy = actor
+        .as_ref()
+        .map(|a| a.supervisor_strategy())
+        .unwrap_or_default();
+
+    // Escalating fails this actor too, until its own supervisor decides
+    if strategy.strategy == Strategy::Escalate {
+        mbox.set_suspended(true);
+    }
+
+    cell.handle_failure(failed, strategy)
 }
 
 fn handle_evt<A>(
@@ -322,6 +463,15 @@ fn handle_evt<A>(
     }
 }
 
+fn handle_terminated<A>(terminated: BasicActorRef, ctx: &Context<A::Msg>, actor: &mut Option<A>)
+where
+    A: Actor,
+{
+    if let Some(actor) = actor.as_mut() {
+        actor.sys_recv(ctx, SystemMsg::Terminated(terminated), None);
+    }
+}
+
 struct Sentinel<'a, Msg: Message> {
     parent: BasicActorRef,
     actor: BasicActorRef,
@@ -351,11 +501,16 @@ pub fn flush_to_deadletters<Msg>(mbox: &Mailbox<Msg>, actor: &BasicActorRef, sys
 where
     Msg: Message,
 {
-    while let Ok(Envelope { msg, sender }) = mbox.try_dequeue() {
+    while let Ok(Envelope { msg, sender, .. }) = mbox.try_dequeue() {
+        if is_dead_letter(&msg) {
+            continue;
+        }
+
         let dl = DeadLetter {
             msg: format!("{:?}", msg),
             sender,
             recipient: actor.clone(),
+            reason: DeadLetterReason::Terminated,
         };
 
         sys.dead_letters().tell(
@@ -368,15 +523,127 @@ where
     }
 }
 
+/// How many messages an actor's mailbox can hold.
+///
+/// Set per actor with `Props::with_mailbox`. System messages are not counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MailboxCapacity {
+    /// Any number of messages
+    #[default]
+    Unbounded,
+
+    /// At most `capacity` messages, with `overflow` applied to messages sent to a full mailbox
+    Bounded {
+        capacity: usize,
+        overflow: OverflowPolicy,
+    },
+}
+
+impl MailboxCapacity {
+    pub fn bounded(capacity: usize, overflow: OverflowPolicy) -> Self {
+        MailboxCapacity::Bounded { capacity, overflow }
+    }
+}
+
+/// What happens when a message is sent to a full bounded mailbox.
+///
+/// Discarded messages are published to dead letters with `DeadLetterReason::MailboxFull`.
+/// `ActorRef::send` ignores the policy and waits until there is space instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Discard the message being sent
+    DropNewest,
+
+    /// Discard the oldest message in the mailbox to make room for the message being sent
+    DropOldest,
+
+    /// Discard the message being sent and return `SendError::Full` from `ActorRef::try_tell`
+    Reject,
+}
+
+/// Compares two messages by priority for `MailboxKind::Priority`
+pub type PriorityFn<Msg> = Arc<dyn Fn(&Msg, &Msg) -> std::cmp::Ordering + Send + Sync>;
+
+/// Identifies control messages for `MailboxKind::ControlAware`
+pub type IsControlFn<Msg> = Arc<dyn Fn(&Msg) -> bool + Send + Sync>;
+
+/// The order in which an actor receives the messages in its mailbox.
+///
+/// Set per actor with `Props::with_mailbox_kind`.
+/// System messages are always received before any other message.
+#[derive(Default)]
+pub enum MailboxKind<Msg> {
+    /// In the order they were sent
+    #[default]
+    Fifo,
+
+    /// By priority, highest first, and in the order they were sent if of equal priority.
+    ///
+    /// The function returns `Ordering::Greater` if the first message has a higher priority.
+    Priority(PriorityFn<Msg>),
+
+    /// Messages for which the function returns `true` before all others,
+    /// each group in the order they were sent
+    ControlAware(IsControlFn<Msg>),
+}
+
+impl<Msg: Message> MailboxKind<Msg> {
+    /// A priority mailbox ordered by `cmp`.
+    pub fn priority<F>(cmp: F) -> Self
+    where
+        F: Fn(&Msg, &Msg) -> std::cmp::Ordering + Send + Sync + 'static,
+    {
+        MailboxKind::Priority(Arc::new(cmp))
+    }
+
+    /// A control-aware mailbox receiving control messages first.
+    pub fn control_aware() -> Self
+    where
+        Msg: ControlMessage,
+    {
+        MailboxKind::ControlAware(Arc::new(ControlMessage::is_control))
+    }
+}
+
+impl<Msg> Clone for MailboxKind<Msg> {
+    fn clone(&self) -> Self {
+        match self {
+            MailboxKind::Fifo => MailboxKind::Fifo,
+            MailboxKind::Priority(cmp) => MailboxKind::Priority(cmp.clone()),
+            MailboxKind::ControlAware(is_control) => MailboxKind::ControlAware(is_control.clone()),
+        }
+    }
+}
+
+impl<Msg> fmt::Debug for MailboxKind<Msg> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MailboxKind::Fifo => f.write_str("Fifo"),
+            MailboxKind::Priority(_) => f.write_str("Priority"),
+            MailboxKind::ControlAware(_) => f.write_str("ControlAware"),
+        }
+    }
+}
+
+/// Messages that can jump ahead of other messages in a control-aware mailbox.
+///
+/// Typically implemented for an actor's message enum, returning `true`
+/// for variants such as shutdown or cancellation requests.
+pub trait ControlMessage {
+    fn is_control(&self) -> bool;
+}
+
 #[derive(Clone, Debug)]
 pub struct MailboxConfig {
     pub msg_process_limit: u32,
+    pub stash_capacity: usize,
 }
 
 impl Default for MailboxConfig {
     fn default() -> Self {
         MailboxConfig {
             msg_process_limit: 1000,
+            stash_capacity: 1000,
         }
     }
 }
@@ -385,8 +652,17 @@ impl MailboxConfig {
     // Option<()> allow to use ? for parsing toml value, ignore it
     pub fn merge(&mut self, v: &toml::Value) -> Option<()> {
         let v = v.as_table()?;
-        let msg_process_limit = v.get("msg_process_limit")?.as_integer()?;
-        self.msg_process_limit = msg_process_limit as u32;
+        if let Some(msg_process_limit) = v.get("msg_process_limit").and_then(|v| v.as_integer()) {
+            self.msg_process_limit = msg_process_limit as u32;
+        }
+        // a negative capacity is ignored
+        if let Some(stash_capacity) = v
+            .get("stash_capacity")
+            .and_then(|v| v.as_integer())
+            .and_then(|v| usize::try_from(v).ok())
+        {
+            self.stash_capacity = stash_capacity;
+        }
         None
     }
 }
@@ -1,18 +1,20 @@
 use slog::Logger;
 
 use std::{
+    any::TypeId,
     collections::HashSet,
-    sync::{Arc, RwLock},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
 };
 
 use crate::{
     actor::actor_cell::{ActorCell, ExtendedCell},
     actor::*,
     kernel::kernel,
-    kernel::mailbox::mailbox,
-    kernel::KernelMsg,
-    system::{ActorSystem, SendingBackend, SysActors, SystemMsg},
-    validate::validate_name,
+    kernel::mailbox::{mailbox, MailboxCapacity, MailboxKind},
+    system::{ActorSystem, SysActors, SystemMsg},
 };
 
 #[derive(Clone)]
@@ -23,12 +25,14 @@ pub struct Provider {
 
 struct ProviderInner {
     paths: RwLock<HashSet<ActorPath>>,
+    anon_names: AtomicU64,
 }
 
 impl Provider {
     pub fn new(log: Logger) -> Self {
         let inner = ProviderInner {
             paths: RwLock::new(HashSet::default()),
+            anon_names: AtomicU64::new(0),
         };
 
         Provider {
@@ -47,9 +51,40 @@ impl Provider {
     where
         A: Actor + 'static,
     {
-        validate_name(name)?;
+        // names starting with `$` are reserved for anonymous actors
+        if name.starts_with('$') {
+            return Err(CreateError::InvalidName(name.into()));
+        }
+
+        self.create_named_actor(props, name, parent, sys)
+    }
 
-        let path = ActorPath::new(&format!("{}/{}", parent.path(), name));
+    /// Creates an actor with a generated name, such as `$b4`,
+    /// that is unique among all actors of the system.
+    pub fn create_anon_actor<A>(
+        &self,
+        props: BoxActorProd<A>,
+        parent: &BasicActorRef,
+        sys: &ActorSystem,
+    ) -> Result<ActorRef<A::Msg>, CreateError>
+    where
+        A: Actor + 'static,
+    {
+        let name = self.anon_name();
+        self.create_named_actor(props, &name, parent, sys)
+    }
+
+    fn create_named_actor<A>(
+        &self,
+        props: BoxActorProd<A>,
+        name: &str,
+        parent: &BasicActorRef,
+        sys: &ActorSystem,
+    ) -> Result<ActorRef<A::Msg>, CreateError>
+    where
+        A: Actor + 'static,
+    {
+        let path = parent.path().child(name)?;
         slog::trace!(sys.log(), "Attempting to create actor at: {}", path);
 
         self.register(&path)?;
@@ -60,7 +95,12 @@ impl Provider {
             host: sys.host(),
         };
 
-        let (sender, sys_sender, mb) = mailbox::<A::Msg>(sys.sys_settings().msg_process_limit);
+        let (capacity, kind) = {
+            let props = props.lock().unwrap();
+            (props.mailbox_capacity(), props.mailbox_kind())
+        };
+        let (sender, sys_sender, mb) =
+            mailbox::<A::Msg>(sys.sys_settings().msg_process_limit, capacity, kind);
 
         let cell = ExtendedCell::new(
             uri,
@@ -70,6 +110,7 @@ impl Provider {
             Arc::new(sender.clone()),
             sys_sender,
             sender,
+            TypeId::of::<A>(),
         );
 
         let k = kernel(props, cell.clone(), mb, sys)?;
@@ -83,6 +124,22 @@ impl Provider {
         Ok(actor)
     }
 
+    /// `$` followed by the next value of a counter, in base 36.
+    fn anon_name(&self) -> String {
+        let mut n = self.inner.anon_names.fetch_add(1, Ordering::Relaxed);
+        let mut digits = Vec::new();
+        loop {
+            digits.push(std::char::from_digit((n % 36) as u32, 36).unwrap());
+            n /= 36;
+            if n == 0 {
+                break;
+            }
+        }
+        std::iter::once('$')
+            .chain(digits.into_iter().rev())
+            .collect()
+    }
+
     fn register(&self, path: &ActorPath) -> Result<(), CreateError> {
         let not_present = self.inner.paths.write().unwrap().insert(path.clone());
         if !not_present {
@@ -97,15 +154,13 @@ impl Provider {
     }
 }
 
-pub fn create_root(
-    sys: &ActorSystem,
-    shutdown_tx: Arc<dyn SendingBackend + Send + Sync + 'static>,
-) -> SysActors {
+pub fn create_root(sys: &ActorSystem) -> SysActors {
     let root = root(sys);
 
     SysActors {
-        user: guardian("user", "/user", &root, sys, Some(shutdown_tx)),
-        sysm: guardian("system", "/system", &root, sys, None),
+        user: guardian("user", &root, sys),
+        sysm: guardian("system", &root, sys),
+        temp: guardian("temp", &root, sys),
         root,
     }
 }
@@ -113,10 +168,11 @@ pub fn create_root(
 fn root(sys: &ActorSystem) -> BasicActorRef {
     let uri = ActorUri {
         name: Arc::from("root"),
-        path: ActorPath::new("/"),
+        path: ActorPath::root(),
         host: Arc::from("localhost"),
     };
-    let (sender, sys_sender, _mb) = mailbox::<SystemMsg>(100);
+    let (sender, sys_sender, _mb) =
+        mailbox::<SystemMsg>(100, MailboxCapacity::Unbounded, MailboxKind::Fifo);
 
     // Big bang: all actors have a parent.
     // This means root also needs a parent.
@@ -141,8 +197,9 @@ fn root(sys: &ActorSystem) -> BasicActorRef {
 
     // root
     let props: BoxActorProd<Guardian> =
-        Props::new_args::<Guardian, _>(("root".to_string(), sys.log(), None));
-    let (sender, sys_sender, mb) = mailbox::<SystemMsg>(100);
+        Props::new_args::<Guardian, _>(("root".to_string(), sys.log()));
+    let (sender, sys_sender, mb) =
+        mailbox::<SystemMsg>(100, MailboxCapacity::Unbounded, MailboxKind::Fifo);
 
     let cell = ExtendedCell::new(
         uri,
@@ -152,6 +209,7 @@ fn root(sys: &ActorSystem) -> BasicActorRef {
         Arc::new(sender.clone()),
         sys_sender,
         sender,
+        TypeId::of::<Guardian>(),
     );
 
     let k = kernel(props, cell.clone(), mb, sys).unwrap();
@@ -161,22 +219,17 @@ fn root(sys: &ActorSystem) -> BasicActorRef {
     BasicActorRef::from(actor_ref)
 }
 
-fn guardian(
-    name: &str,
-    path: &str,
-    root: &BasicActorRef,
-    sys: &ActorSystem,
-    shutdown_tx: Option<Arc<dyn SendingBackend + Send + Sync + 'static>>,
-) -> BasicActorRef {
+fn guardian(name: &str, root: &BasicActorRef, sys: &ActorSystem) -> BasicActorRef {
     let uri = ActorUri {
         name: Arc::from(name),
-        path: ActorPath::new(path),
+        path: root.path().child(name).unwrap(),
         host: Arc::from("localhost"),
     };
 
     let props: BoxActorProd<Guardian> =
-        Props::new_args::<Guardian, _>((name.to_string(), sys.log(), shutdown_tx));
-    let (sender, sys_sender, mb) = mailbox::<SystemMsg>(100);
+        Props::new_args::<Guardian, _>((name.to_string(), sys.log()));
+    let (sender, sys_sender, mb) =
+        mailbox::<SystemMsg>(100, MailboxCapacity::Unbounded, MailboxKind::Fifo);
 
     let cell = ExtendedCell::new(
         uri,
@@ -186,6 +239,7 @@ fn guardian(
         Arc::new(sender.clone()),
         sys_sender,
         sender,
+        TypeId::of::<Guardian>(),
     );
 
     let k = kernel(props, cell.clone(), mb, sys).unwrap();
@@ -200,28 +254,11 @@ fn guardian(
 struct Guardian {
     name: String,
     log: Logger,
-    shutdown_tx: Option<Arc<dyn SendingBackend + Send + Sync + 'static>>,
 }
 
-impl
-    ActorFactoryArgs<(
-        String,
-        Logger,
-        Option<Arc<dyn SendingBackend + Send + Sync + 'static>>,
-    )> for Guardian
-{
-    fn create_args(
-        (name, log, shutdown_tx): (
-            String,
-            Logger,
-            Option<Arc<dyn SendingBackend + Send + Sync + 'static>>,
-        ),
-    ) -> Self {
-        Guardian {
-            name,
-            log,
-            shutdown_tx,
-        }
+impl ActorFactoryArgs<(String, Logger)> for Guardian {
+    fn create_args((name, log): (String, Logger)) -> Self {
+        Guardian { name, log }
     }
 }
 
@@ -231,9 +268,6 @@ impl Actor for Guardian {
     fn recv(&mut self, _: &Context<Self::Msg>, _: Self::Msg, _: Option<BasicActorRef>) {}
 
     fn post_stop(&mut self) {
-        if let Some(tx) = self.shutdown_tx.as_ref() {
-            tx.send_msg(KernelMsg::Sys);
-        }
         slog::trace!(self.log, "{} guardian stopped", self.name);
     }
 }
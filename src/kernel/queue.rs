@@ -1,84 +1,345 @@
-use std::sync::{
-    mpsc::{channel, Receiver, Sender},
-    Arc, Mutex,
+use std::{
+    cmp::Ordering as CmpOrdering,
+    collections::{BTreeSet, VecDeque},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
 };
 
-use crate::{Envelope, Message};
+use tokio::sync::Notify;
+
+use crate::{
+    actor::DeadLetterReason,
+    kernel::mailbox::{IsControlFn, MailboxKind, PriorityFn},
+    Envelope, Message,
+};
 
 pub fn queue<Msg: Message>() -> (QueueWriter<Msg>, QueueReader<Msg>) {
-    let (tx, rx) = channel::<Envelope<Msg>>();
+    queue_with(None, MailboxKind::Fifo)
+}
 
-    let qw = QueueWriter {
-        tx: Arc::new(Mutex::new(tx)),
+/// Creates a queue holding at most `capacity` messages, or any number if `None`,
+/// ordered according to `kind`.
+pub fn queue_with<Msg: Message>(
+    capacity: Option<usize>,
+    kind: MailboxKind<Msg>,
+) -> (QueueWriter<Msg>, QueueReader<Msg>) {
+    let items = match kind {
+        MailboxKind::Fifo => Items::Fifo(VecDeque::new()),
+        MailboxKind::Priority(cmp) => Items::Priority {
+            items: BTreeSet::new(),
+            cmp,
+            front: 0,
+            back: 0,
+        },
+        MailboxKind::ControlAware(is_control) => Items::ControlAware {
+            control: VecDeque::new(),
+            normal: VecDeque::new(),
+            is_control,
+        },
     };
 
-    let qr = QueueReaderInner {
-        rx,
-        next_item: None,
-    };
+    let inner = Arc::new(QueueInner {
+        items: Mutex::new(items),
+        capacity,
+        closed: AtomicBool::new(false),
+        space: Notify::new(),
+    });
 
-    let qr = QueueReader {
-        inner: Mutex::new(qr),
+    let qw = QueueWriter {
+        inner: inner.clone(),
     };
 
+    let qr = QueueReader { inner };
+
     (qw, qr)
 }
 
+struct QueueInner<Msg: Message> {
+    items: Mutex<Items<Msg>>,
+    capacity: Option<usize>,
+    closed: AtomicBool,
+    space: Notify,
+}
+
+impl<Msg: Message> QueueInner<Msg> {
+    fn is_full(&self, items: &Items<Msg>) -> bool {
+        matches!(self.capacity, Some(capacity) if items.len() >= capacity)
+    }
+}
+
+/// Queued messages, kept in the order they are dequeued.
+enum Items<Msg: Message> {
+    Fifo(VecDeque<Envelope<Msg>>),
+    Priority {
+        items: BTreeSet<Prioritized<Msg>>,
+        cmp: PriorityFn<Msg>,
+        // the sequence numbers of the messages last added at the front and at the back
+        front: i64,
+        back: i64,
+    },
+    ControlAware {
+        control: VecDeque<Envelope<Msg>>,
+        normal: VecDeque<Envelope<Msg>>,
+        is_control: IsControlFn<Msg>,
+    },
+}
+
+impl<Msg: Message> Items<Msg> {
+    fn push(&mut self, msg: Envelope<Msg>) {
+        match self {
+            Items::Fifo(items) => items.push_back(msg),
+            Items::Priority {
+                items, cmp, back, ..
+            } => {
+                // after all messages of higher or equal priority
+                *back += 1;
+                items.insert(Prioritized::new(msg, *back, cmp));
+            }
+            Items::ControlAware {
+                control,
+                normal,
+                is_control,
+            } => {
+                if is_control(&msg.msg) {
+                    control.push_back(msg);
+                } else {
+                    normal.push_back(msg);
+                }
+            }
+        }
+    }
+
+    /// Adds `msg` ahead of the queued messages it would otherwise follow.
+    fn push_front(&mut self, msg: Envelope<Msg>) {
+        match self {
+            Items::Fifo(items) => items.push_front(msg),
+            Items::Priority {
+                items, cmp, front, ..
+            } => {
+                // after all messages of higher priority
+                *front -= 1;
+                items.insert(Prioritized::new(msg, *front, cmp));
+            }
+            Items::ControlAware {
+                control,
+                normal,
+                is_control,
+            } => {
+                if is_control(&msg.msg) {
+                    control.push_front(msg);
+                } else {
+                    normal.push_front(msg);
+                }
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Option<Envelope<Msg>> {
+        match self {
+            Items::Fifo(items) => items.pop_front(),
+            Items::Priority { items, .. } => items.pop_first().map(|item| item.msg),
+            Items::ControlAware {
+                control, normal, ..
+            } => control.pop_front().or_else(|| normal.pop_front()),
+        }
+    }
+
+    /// Removes the message to discard when the queue overflows.
+    fn evict(&mut self) -> Option<Envelope<Msg>> {
+        match self {
+            Items::Fifo(items) => items.pop_front(),
+            Items::Priority { items, .. } => items.pop_last().map(|item| item.msg),
+            Items::ControlAware {
+                control, normal, ..
+            } => normal.pop_front().or_else(|| control.pop_front()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Items::Fifo(items) => items.len(),
+            Items::Priority { items, .. } => items.len(),
+            Items::ControlAware {
+                control, normal, ..
+            } => control.len() + normal.len(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A message of a priority queue, ordered by priority, then by sequence number
+/// among messages of equal priority.
+struct Prioritized<Msg: Message> {
+    msg: Envelope<Msg>,
+    seq: i64,
+    cmp: PriorityFn<Msg>,
+}
+
+impl<Msg: Message> Prioritized<Msg> {
+    fn new(msg: Envelope<Msg>, seq: i64, cmp: &PriorityFn<Msg>) -> Self {
+        Prioritized {
+            msg,
+            seq,
+            cmp: cmp.clone(),
+        }
+    }
+}
+
+impl<Msg: Message> Ord for Prioritized<Msg> {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        // messages of higher priority come first
+        (self.cmp)(&other.msg.msg, &self.msg.msg).then(self.seq.cmp(&other.seq))
+    }
+}
+
+impl<Msg: Message> PartialOrd for Prioritized<Msg> {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Msg: Message> PartialEq for Prioritized<Msg> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl<Msg: Message> Eq for Prioritized<Msg> {}
+
 #[derive(Clone)]
 pub struct QueueWriter<Msg: Message> {
-    tx: Arc<Mutex<Sender<Envelope<Msg>>>>,
+    inner: Arc<QueueInner<Msg>>,
 }
 
 impl<Msg: Message> QueueWriter<Msg> {
+    /// Adds `msg` to the queue, failing if the queue is full or closed.
     pub fn try_enqueue(&self, msg: Envelope<Msg>) -> EnqueueResult<Msg> {
-        self.tx
-            .lock()
-            .unwrap()
-            .send(msg)
-            .map(|_| ())
-            .map_err(|e| EnqueueError { msg: e.0 })
+        let mut items = self.inner.items.lock().unwrap();
+        if self.inner.closed.load(Ordering::Acquire) {
+            Err(EnqueueError::new(msg, DeadLetterReason::Terminated))
+        } else if self.inner.is_full(&items) {
+            Err(EnqueueError::new(msg, DeadLetterReason::MailboxFull))
+        } else {
+            items.push(msg);
+            Ok(None)
+        }
     }
-}
 
-pub struct QueueReader<Msg: Message> {
-    inner: Mutex<QueueReaderInner<Msg>>,
+    /// Adds `msg` to the queue, removing and returning a queued message if the queue is full.
+    ///
+    /// The removed message is the oldest one, except in a priority queue where
+    /// it is the one with the lowest priority, which may be `msg` itself,
+    /// and in a control-aware queue where it is the oldest non-control message if there is one.
+    pub fn enqueue_evicting(&self, msg: Envelope<Msg>) -> EnqueueResult<Msg> {
+        let mut items = self.inner.items.lock().unwrap();
+        if self.inner.closed.load(Ordering::Acquire) {
+            return Err(EnqueueError::new(msg, DeadLetterReason::Terminated));
+        }
+
+        let evicted = if self.inner.is_full(&items) {
+            items.push(msg);
+            items.evict()
+        } else {
+            items.push(msg);
+            None
+        };
+        Ok(evicted)
+    }
+
+    /// Adds `msgs` to the head of the queue, keeping their order.
+    ///
+    /// The capacity of the queue is not applied.
+    pub fn enqueue_front(
+        &self,
+        msgs: Vec<Envelope<Msg>>,
+    ) -> Result<(), EnqueueError<Vec<Envelope<Msg>>>> {
+        let mut items = self.inner.items.lock().unwrap();
+        if self.inner.closed.load(Ordering::Acquire) {
+            return Err(EnqueueError::new(msgs, DeadLetterReason::Terminated));
+        }
+
+        for msg in msgs.into_iter().rev() {
+            items.push_front(msg);
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.items.lock().unwrap().len()
+    }
+
+    /// Adds `msg` to the queue, waiting for space if the queue is full.
+    ///
+    /// Fails only if the queue is closed.
+    pub async fn enqueue(&self, mut msg: Envelope<Msg>) -> EnqueueResult<Msg> {
+        loop {
+            // register before checking so that a dequeue or close
+            // happening in between is not missed
+            let space = self.inner.space.notified();
+            match self.try_enqueue(msg) {
+                Err(EnqueueError {
+                    msg: rejected,
+                    reason: DeadLetterReason::MailboxFull,
+                }) => msg = rejected,
+                result => return result,
+            }
+            space.await;
+        }
+    }
 }
 
-struct QueueReaderInner<Msg: Message> {
-    rx: Receiver<Envelope<Msg>>,
-    next_item: Option<Envelope<Msg>>,
+pub struct QueueReader<Msg: Message> {
+    inner: Arc<QueueInner<Msg>>,
 }
 
 impl<Msg: Message> QueueReader<Msg> {
     pub fn try_dequeue(&self) -> DequeueResult<Envelope<Msg>> {
-        let mut inner = self.inner.lock().unwrap();
-        if let Some(item) = inner.next_item.take() {
-            Ok(item)
-        } else {
-            inner.rx.try_recv().map_err(|_| QueueEmpty)
+        let item = self.inner.items.lock().unwrap().pop();
+        match item {
+            Some(item) => {
+                if self.inner.capacity.is_some() {
+                    self.inner.space.notify_one();
+                }
+                Ok(item)
+            }
+            None => Err(QueueEmpty),
         }
     }
 
     pub fn has_msgs(&self) -> bool {
-        let mut inner = self.inner.lock().unwrap();
-        inner.next_item.is_some() || {
-            match inner.rx.try_recv() {
-                Ok(item) => {
-                    inner.next_item = Some(item);
-                    true
-                }
-                Err(_) => false,
-            }
-        }
+        !self.inner.items.lock().unwrap().is_empty()
+    }
+}
+
+impl<Msg: Message> Drop for QueueReader<Msg> {
+    fn drop(&mut self) {
+        // messages can no longer be received
+        let _items = self.inner.items.lock().unwrap();
+        self.inner.closed.store(true, Ordering::Release);
+        self.inner.space.notify_waiters();
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct EnqueueError<T> {
     pub msg: T,
+    pub reason: DeadLetterReason,
+}
+
+impl<T> EnqueueError<T> {
+    pub fn new(msg: T, reason: DeadLetterReason) -> Self {
+        EnqueueError { msg, reason }
+    }
 }
 
-pub type EnqueueResult<Msg> = Result<(), EnqueueError<Envelope<Msg>>>;
+/// `Ok(Some(dropped))` when a message was accepted but `dropped`
+/// was discarded because the queue is full.
+pub type EnqueueResult<Msg> = Result<Option<Envelope<Msg>>, EnqueueError<Envelope<Msg>>>;
 
 pub struct QueueEmpty;
 pub type DequeueResult<Msg> = Result<Msg, QueueEmpty>;
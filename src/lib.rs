@@ -13,12 +13,13 @@ mod config;
 pub mod kernel;
 pub mod system;
 mod tokio_backend;
+mod util;
 
 use std::any::Any;
 use std::fmt;
 use std::fmt::Debug;
 
-use crate::actor::BasicActorRef;
+use crate::{actor::BasicActorRef, system::ScheduleId};
 
 pub use self::config::{load_config, Config};
 
@@ -27,6 +28,8 @@ pub use self::config::{load_config, Config};
 pub struct Envelope<T: Message> {
     pub sender: Option<BasicActorRef>,
     pub msg: T,
+    /// The timer of the receiver that sent the message, dropped if the timer is cancelled meanwhile
+    pub(crate) timer: Option<ScheduleId>,
 }
 
 pub trait Message: Debug + Clone + Send + 'static {}
@@ -90,14 +93,23 @@ impl Debug for AnyMessage {
 pub mod actors {
     pub use crate::actor::{
         actor, channel, Actor, ActorArgs, ActorFactory, ActorFactoryArgs, ActorPath, ActorProducer,
-        ActorRef, ActorRefFactory, ActorReference, ActorUri, All, BasicActorRef, BoxActorProd,
-        BoxedTell, Channel, ChannelMsg, ChannelRef, Context, CreateError, DLChannelMsg, DeadLetter,
-        EventsChannel, Props, Publish, Receive, Sender, Subscribe, SubscribeWithResponse,
-        SubscribedResponse, SysTopic, Tell, Topic, Unsubscribe, UnsubscribeAll,
+        ActorRef, ActorRefFactory, ActorReference, ActorSelection, ActorSelectionFactory, ActorUri,
+        All, AnonActorRefFactory, AskError, Async, AsyncActor, Backoff, BasicActorRef, Behavior,
+        BoxActorProd, BoxFuture, BoxedTell, Broadcast, Channel, ChannelMsg, ChannelRef,
+        ConsistentHashable, Context, CreateError, DLChannelMsg, DeadLetter, DeadLetterReason,
+        EventsChannel, InvalidName, InvalidPath, Props, Publish, Receive, Resizer, RestartLimit,
+        Router, RouterConfig, RouterMsg, RouterRef, RoutingLogic, SendError, Sender, StashError,
+        Strategy, Subscribe, SubscribeWithResponse, SubscribedResponse, SupervisorScope,
+        SupervisorStrategy, SysTopic, Tell, Topic, Unsubscribe, UnsubscribeAll,
+    };
+    pub use crate::kernel::mailbox::{
+        ControlMessage, MailboxCapacity, MailboxKind, OverflowPolicy,
     };
     pub use crate::system::{
-        ActorSystem, ActorSystemBackend, ScheduleId, SendingBackend, SystemBuilder, SystemEvent,
-        SystemMsg, Timer,
+        ActiveTimer, ActorSystem, ActorSystemBackend, CoordinatedShutdown, CronSchedule,
+        InvalidCron, MissedFire, RepeatMode, RepeatSchedule, ScheduleHandle, ScheduleId,
+        SendingBackend, ShutdownPhase, ShutdownReport, SystemBuilder, SystemEvent, SystemMsg,
+        Timer, TimerBackend, TimerRef, VirtualClock,
     };
     pub use crate::tokio_backend::ActorSystemBackendTokio;
     pub use crate::{AnyMessage, Message};
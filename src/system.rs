@@ -1,4 +1,6 @@
+pub(crate) mod cron;
 pub(crate) mod logger;
+pub(crate) mod shutdown;
 pub(crate) mod timer;
 
 use std::fmt;
@@ -6,7 +8,14 @@ use std::fmt;
 use crate::actor::BasicActorRef;
 
 // Public API (plus the pub data types in this file)
-pub use self::timer::{BasicTimer, ScheduleId, Timer};
+pub use self::{
+    cron::{CronSchedule, InvalidCron, MissedFire},
+    shutdown::{CoordinatedShutdown, ShutdownPhase, ShutdownReport, DEFAULT_PHASE_TIMEOUT},
+    timer::{
+        ActiveTimer, BasicTimer, RepeatMode, RepeatSchedule, ScheduleHandle, ScheduleId, Timer,
+        TimerBackend, TimerRef, TokioTimer, VirtualClock,
+    },
+};
 
 #[derive(Clone, Debug)]
 pub enum SystemMsg {
@@ -14,12 +23,15 @@ pub enum SystemMsg {
     Command(SystemCmd),
     Event(SystemEvent),
     Failed(BasicActorRef),
+    /// A watched actor was terminated
+    Terminated(BasicActorRef),
 }
 
 #[derive(Clone, Debug)]
 pub enum SystemCmd {
     Stop,
     Restart,
+    Resume,
 }
 
 impl Into<SystemMsg> for SystemCmd {
@@ -136,6 +148,7 @@ use std::{
     time::{Duration, Instant, SystemTime},
 };
 
+use tokio::sync::OnceCell;
 use uuid::Uuid;
 
 use crate::{
@@ -188,6 +201,7 @@ pub struct SystemBuilder {
     cfg: Option<Config>,
     log: Option<Logger>,
     backend: Option<ActorSystemBackendTokio>,
+    timer: Option<TimerRef>,
 }
 
 impl SystemBuilder {
@@ -203,7 +217,7 @@ impl SystemBuilder {
         let backend = self.backend.unwrap();
         let log = self.log.unwrap_or_else(|| default_log(&cfg));
 
-        ActorSystem::create(name.as_ref(), backend, log, cfg)
+        ActorSystem::create_with_timer(name.as_ref(), backend, log, cfg, self.timer)
     }
 
     pub fn name(self, name: &str) -> Self {
@@ -233,6 +247,15 @@ impl SystemBuilder {
             ..self
         }
     }
+
+    /// Uses `timer` instead of the one selected in the `[scheduler]` configuration,
+    /// such as the timer of a `VirtualClock`.
+    pub fn timer(self, timer: TimerRef) -> Self {
+        SystemBuilder {
+            timer: Some(timer),
+            ..self
+        }
+    }
 }
 
 /// The actor runtime and common services coordinator
@@ -245,15 +268,14 @@ impl SystemBuilder {
 #[derive(Clone)]
 pub struct ActorSystem {
     proto: Arc<ProtoSystem>,
-    sys_actors: Option<SysActors>,
+    sys_actors: Arc<OnceCell<SysActors>>,
     log: Logger,
     debug: bool,
     pub backend: ActorSystemBackendTokio,
     pub timer: Arc<Mutex<TimerRef>>,
-    sys_channels: Option<SysChannels>,
-    temp_storage: Arc<Mutex<Option<(SysActors, SysChannels)>>>,
+    sys_channels: Arc<OnceCell<SysChannels>>,
     pub(super) provider: Provider,
-    shutdown_rx: Arc<Mutex<Option<<ActorSystemBackendTokio as ActorSystemBackend>::Rx>>>,
+    shutdown: CoordinatedShutdown,
 }
 
 impl ActorSystem {
@@ -296,6 +318,16 @@ impl ActorSystem {
         backend: ActorSystemBackendTokio,
         log: Logger,
         cfg: Config,
+    ) -> Result<ActorSystem, SystemError> {
+        ActorSystem::create_with_timer(name, backend, log, cfg, None)
+    }
+
+    fn create_with_timer(
+        name: &str,
+        backend: ActorSystemBackendTokio,
+        log: Logger,
+        cfg: Config,
+        timer: Option<TimerRef>,
     ) -> Result<ActorSystem, SystemError> {
         validate_name(name).map_err(|_| SystemError::InvalidName(name.into()))?;
         // Process Configuration
@@ -307,7 +339,10 @@ impl ActorSystem {
         }
 
         let prov = Provider::new(log.clone());
-        let timer = BasicTimer::start(&cfg);
+        let timer = timer.unwrap_or_else(|| match cfg.scheduler.backend {
+            TimerBackend::Thread => BasicTimer::start(&cfg),
+            TimerBackend::Tokio => TokioTimer::start(&backend),
+        });
 
         // 1. create proto system
         let proto = ProtoSystem {
@@ -317,35 +352,32 @@ impl ActorSystem {
             config: cfg.clone(),
             sys_settings: SystemSettings {
                 msg_process_limit: cfg.mailbox.msg_process_limit,
+                stash_capacity: cfg.mailbox.stash_capacity,
             },
             started_at: SystemTime::now(),
             started_at_moment: Instant::now(),
         };
 
-        let (shutdown_tx, shutdown_rx) = backend.channel(1);
-
         // 2. create uninitialized system
-        let mut sys = ActorSystem {
+        let sys = ActorSystem {
             proto: Arc::new(proto),
             debug,
             backend,
             log,
             // event_store: None,
             timer: Arc::new(Mutex::new(timer)),
-            sys_channels: None,
-            sys_actors: None,
-            temp_storage: Arc::new(Mutex::new(None)),
+            sys_channels: Arc::new(OnceCell::new()),
+            sys_actors: Arc::new(OnceCell::new()),
             provider: prov.clone(),
-            shutdown_rx: Arc::new(Mutex::new(Some(shutdown_rx))),
+            shutdown: CoordinatedShutdown::default(),
         };
 
         // 3. create initial actor hierarchy
-        let sys_actors = create_root(&sys, Arc::new(shutdown_tx));
-        sys.sys_actors = Some(sys_actors.clone());
+        // actors created meanwhile share the system actors and channels once they are set
+        let _ = sys.sys_actors.set(create_root(&sys));
 
         // 4. start system channels
-        let sys_channels = sys_channels(&prov, &sys)?;
-        sys.sys_channels = Some(sys_channels.clone());
+        let _ = sys.sys_channels.set(sys_channels(&prov, &sys)?);
 
         // 5. start dead letter logger
         let _dl_logger = sys_actor_of_args::<DeadLetterLogger, _>(
@@ -355,20 +387,11 @@ impl ActorSystem {
             (sys.dead_letters().clone(), sys.log()),
         )?;
 
-        *sys.temp_storage.lock().unwrap() = Some((sys_actors, sys_channels));
-        sys.sys_actors.as_ref().unwrap().user.sys_init();
-
         slog::debug!(sys.log, "Actor system [{}] [{}] started", sys.id(), name);
 
         Ok(sys)
     }
 
-    pub(crate) fn complete_start(&mut self) {
-        let (sys_actors, sys_channels) = self.temp_storage.lock().unwrap().clone().unwrap();
-        self.sys_actors = Some(sys_actors);
-        self.sys_channels = Some(sys_channels);
-    }
-
     /// Returns the system start moment
     pub fn start_date(&self) -> SystemTime {
         self.proto.started_at
@@ -428,28 +451,69 @@ impl ActorSystem {
     }
 
     /// Returns the system root's actor reference
-    fn root(&self) -> &BasicActorRef {
-        &self.sys_actors.as_ref().unwrap().root
+    pub(crate) fn root(&self) -> &BasicActorRef {
+        &self.sys_actors.get().unwrap().root
     }
 
     /// Returns the user root actor reference
     pub fn user_root(&self) -> &BasicActorRef {
-        &self.sys_actors.as_ref().unwrap().user
+        &self.sys_actors.get().unwrap().user
     }
 
     /// Returns the system root actor reference
     pub fn sys_root(&self) -> &BasicActorRef {
-        &self.sys_actors.as_ref().unwrap().sysm
+        &self.sys_actors.get().unwrap().sysm
+    }
+
+    /// Returns the temp root actor reference
+    ///
+    /// Short-lived helper actors, such as the reply targets of `ask`, are created under it.
+    pub fn temp_root(&self) -> &BasicActorRef {
+        &self.sys_actors.get().unwrap().temp
+    }
+
+    /// Creates a short-lived helper actor under the temp root, with a generated name.
+    ///
+    /// See `AnonActorRefFactory::actor_of_props_anon`.
+    pub fn temp_actor_of_props<A>(
+        &self,
+        props: BoxActorProd<A>,
+    ) -> Result<ActorRef<A::Msg>, CreateError>
+    where
+        A: Actor,
+    {
+        self.provider
+            .create_anon_actor(props, self.temp_root(), self)
+    }
+
+    /// Creates a short-lived helper actor under the temp root, with a generated name.
+    pub fn temp_actor_of<A>(&self) -> Result<ActorRef<<A as Actor>::Msg>, CreateError>
+    where
+        A: ActorFactory,
+    {
+        self.temp_actor_of_props(Props::new::<A>())
+    }
+
+    /// Creates a short-lived helper actor under the temp root, with a generated name.
+    pub fn temp_actor_of_args<A, Args>(
+        &self,
+        args: Args,
+    ) -> Result<ActorRef<<A as Actor>::Msg>, CreateError>
+    where
+        Args: ActorArgs,
+        A: ActorFactoryArgs<Args>,
+    {
+        self.temp_actor_of_props(Props::new_args::<A, _>(args))
     }
 
     /// Returns a reference to the system events channel
     pub fn sys_events(&self) -> &ActorRef<ChannelMsg<SystemEvent>> {
-        &self.sys_channels.as_ref().unwrap().sys_events
+        &self.sys_channels.get().unwrap().sys_events
     }
 
     /// Returns a reference to the dead letters channel
     pub fn dead_letters(&self) -> &ActorRef<DLChannelMsg> {
-        &self.sys_channels.as_ref().unwrap().dead_letters
+        &self.sys_channels.get().unwrap().dead_letters
     }
 
     pub fn publish_event(&self, evt: SystemEvent) {
@@ -471,22 +535,22 @@ impl ActorSystem {
         self.log.clone()
     }
 
+    /// Returns the coordinated shutdown, to register tasks and set timeouts of its phases
+    pub fn coordinated_shutdown(&self) -> &CoordinatedShutdown {
+        &self.shutdown
+    }
+
     /// Shutdown the actor system
     ///
-    /// Attempts a graceful shutdown of the system and all actors.
+    /// Attempts a graceful shutdown of the system and all actors,
+    /// running the phases of the coordinated shutdown in order.
     /// Actors will receive a stop message, executing `actor.post_stop`.
     ///
-    /// Does not block. Returns a future which is completed when all
-    /// actors have successfully stopped.
-    pub fn shutdown(&self) -> Pin<Box<dyn Future<Output = ()>>> {
-        self.stop(self.user_root());
-        self.backend.receiver_future(
-            self.shutdown_rx
-                .lock()
-                .unwrap()
-                .take()
-                .expect("shutdown was already called"),
-        )
+    /// Does not block. Returns a future which is completed when all phases
+    /// have completed or timed out, reporting what did not complete in time.
+    /// The system's timer is stopped and can't be used after shutdown.
+    pub fn shutdown(&self) -> Pin<Box<dyn Future<Output = ShutdownReport> + Send>> {
+        self.shutdown.run(self.clone())
     }
 }
 
@@ -529,6 +593,19 @@ impl ActorRefFactory for ActorSystem {
     }
 }
 
+impl AnonActorRefFactory for ActorSystem {
+    fn actor_of_props_anon<A>(
+        &self,
+        props: BoxActorProd<A>,
+    ) -> Result<ActorRef<A::Msg>, CreateError>
+    where
+        A: Actor,
+    {
+        self.provider
+            .create_anon_actor(props, self.user_root(), self)
+    }
+}
+
 impl ActorRefFactory for &ActorSystem {
     fn actor_of_props<A>(
         &self,
@@ -568,6 +645,26 @@ impl ActorRefFactory for &ActorSystem {
     }
 }
 
+impl AnonActorRefFactory for &ActorSystem {
+    fn actor_of_props_anon<A>(
+        &self,
+        props: BoxActorProd<A>,
+    ) -> Result<ActorRef<A::Msg>, CreateError>
+    where
+        A: Actor,
+    {
+        self.provider
+            .create_anon_actor(props, self.user_root(), self)
+    }
+}
+
+impl ActorSelectionFactory for ActorSystem {
+    /// Selects actors by path, resolving a relative path from the user root.
+    fn select(&self, path: &str) -> Result<ActorSelection, InvalidPath> {
+        ActorSelection::new(self.user_root().clone(), path)
+    }
+}
+
 impl fmt::Debug for ActorSystem {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -581,14 +678,13 @@ impl fmt::Debug for ActorSystem {
 }
 
 impl Timer for ActorSystem {
-    fn schedule<T, M>(
+    fn schedule_with<T, M>(
         &self,
-        initial_delay: Duration,
-        interval: Duration,
+        schedule: RepeatSchedule,
         receiver: ActorRef<M>,
         sender: Sender,
         msg: T,
-    ) -> ScheduleId
+    ) -> ScheduleHandle
     where
         T: Message + Into<M>,
         M: Message,
@@ -596,17 +692,11 @@ impl Timer for ActorSystem {
         let id = Uuid::new_v4();
         let msg: M = msg.into();
 
-        let job = RepeatJob {
-            id,
-            send_at: Instant::now() + initial_delay,
-            interval,
-            receiver: receiver.into(),
-            sender,
-            msg: AnyMessage::new(msg, false),
-        };
+        let msg = AnyMessage::new(msg, false);
+        let job = schedule.into_job(id, receiver.into(), sender, msg, None);
 
-        let _ = self.timer.lock().unwrap().send(Job::Repeat(job));
-        id
+        let _ = self.timer.lock().unwrap().send(Job::Repeat(Box::new(job)));
+        ScheduleHandle::new(id, self.timer.clone(), None)
     }
 
     fn schedule_once<T, M>(
@@ -615,7 +705,7 @@ impl Timer for ActorSystem {
         receiver: ActorRef<M>,
         sender: Sender,
         msg: T,
-    ) -> ScheduleId
+    ) -> ScheduleHandle
     where
         T: Message + Into<M>,
         M: Message,
@@ -629,10 +719,38 @@ impl Timer for ActorSystem {
             receiver: receiver.into(),
             sender,
             msg: AnyMessage::new(msg, true),
+            owner: None,
         };
 
         let _ = self.timer.lock().unwrap().send(Job::Once(job));
-        id
+        ScheduleHandle::new(id, self.timer.clone(), None)
+    }
+
+    fn schedule_cron<T, M>(
+        &self,
+        schedule: CronSchedule,
+        receiver: ActorRef<M>,
+        sender: Sender,
+        msg: T,
+    ) -> ScheduleHandle
+    where
+        T: Message + Into<M>,
+        M: Message,
+    {
+        let id = Uuid::new_v4();
+        let msg: M = msg.into();
+
+        let job = CronJob {
+            id,
+            schedule,
+            receiver: receiver.into(),
+            sender,
+            msg: AnyMessage::new(msg, false),
+            owner: None,
+        };
+
+        let _ = self.timer.lock().unwrap().send(Job::Cron(Box::new(job)));
+        ScheduleHandle::new(id, self.timer.clone(), None)
     }
 
     fn cancel_schedule(&self, id: Uuid) {
@@ -685,6 +803,7 @@ fn sys_channels(prov: &Provider, sys: &ActorSystem) -> Result<SysChannels, Syste
 
 pub struct SystemSettings {
     pub msg_process_limit: u32,
+    pub stash_capacity: usize,
 }
 
 #[derive(Clone)]
@@ -692,6 +811,7 @@ pub struct SysActors {
     pub root: BasicActorRef,
     pub user: BasicActorRef,
     pub sysm: BasicActorRef,
+    pub temp: BasicActorRef,
 }
 
 #[derive(Clone)]
@@ -0,0 +1,289 @@
+use std::{
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const SECS_PER_DAY: i64 = 86_400;
+
+// a matching day occurs at least every 8 years, as for February 29 when 2100 is skipped
+const MAX_DAYS: i64 = 366 * 9;
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+const WEEKDAYS: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// What a cron schedule does when some of its fire times were missed,
+/// because the system was suspended or its clock moved forward.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissedFire {
+    /// Send a single message for all the missed fire times
+    #[default]
+    FireOnce,
+
+    /// Send no message for the missed fire times
+    Skip,
+
+    /// Send a message for each missed fire time
+    FireAll,
+}
+
+/// A cron expression, giving the times at which a message is sent
+/// with `Timer::schedule_cron`.
+///
+/// The expression has five fields, `minute hour day-of-month month day-of-week`,
+/// or six with a leading `second` field. Each field is `*`, a value, a range `a-b`,
+/// or a list of those separated by `,`, optionally followed by a step `/n`.
+/// Months and days of the week can be given by their first three letters,
+/// and Sunday is either 0 or 7. When both the day of the month and the day of
+/// the week are restricted, a day matching either of them matches. As in Vixie
+/// cron, a field starting with `*`, such as `*/2`, is not restricted in this sense,
+/// so `0 0 */2 * MON` fires on Mondays that fall on an odd day of the month.
+///
+/// Times are evaluated in UTC, unless a fixed offset is set with `with_utc_offset`.
+///
+/// # Examples
+///
+/// ```
+/// # use tezedge_actor_system::actors::*;
+/// # use std::time::{Duration, UNIX_EPOCH};
+///
+/// // at 03:30 on weekdays, in UTC+02:00
+/// let schedule: CronSchedule = "30 3 * * MON-FRI".parse().unwrap();
+/// let schedule = schedule.with_utc_offset(2 * 3600).on_missed(MissedFire::Skip);
+///
+/// // Thursday, 1 January 1970, 01:30 UTC
+/// let next = schedule.next_after(UNIX_EPOCH).unwrap();
+/// assert_eq!(next, UNIX_EPOCH + Duration::from_secs(5400));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronSchedule {
+    expr: String,
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+    offset: i32,
+    missed: MissedFire,
+}
+
+impl CronSchedule {
+    /// Evaluates the schedule at a fixed offset from UTC, in seconds east of UTC.
+    pub fn with_utc_offset(self, offset: i32) -> Self {
+        CronSchedule { offset, ..self }
+    }
+
+    /// Sets what the schedule does when fire times were missed.
+    pub fn on_missed(self, missed: MissedFire) -> Self {
+        CronSchedule { missed, ..self }
+    }
+
+    /// The expression the schedule was parsed from.
+    pub fn as_str(&self) -> &str {
+        &self.expr
+    }
+
+    pub fn utc_offset(&self) -> i32 {
+        self.offset
+    }
+
+    pub fn missed_fire(&self) -> MissedFire {
+        self.missed
+    }
+
+    /// The first fire time strictly after `time`,
+    /// or `None` if the expression never matches, such as on February 30.
+    pub fn next_after(&self, time: SystemTime) -> Option<SystemTime> {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs_f64().ceil() as i64),
+        };
+
+        let local = secs + 1 + i64::from(self.offset);
+        let start = local.div_euclid(SECS_PER_DAY);
+        let mut from = local.rem_euclid(SECS_PER_DAY) as u32;
+
+        for days in start..start + MAX_DAYS {
+            let (month, day) = month_day(days);
+            // 1 January 1970 was a Thursday
+            let weekday = (days + 4).rem_euclid(7) as u32;
+
+            if has(self.months, month) && self.matches_day(day, weekday) {
+                if let Some(time) = self.first_time_from(from) {
+                    let local = days * SECS_PER_DAY + i64::from(time);
+                    return Some(from_unix(local - i64::from(self.offset)));
+                }
+            }
+
+            from = 0;
+        }
+        None
+    }
+
+    fn matches_day(&self, day: u32, weekday: u32) -> bool {
+        let day = has(self.days, day);
+        let weekday = has(self.weekdays, weekday);
+        if self.any_day || self.any_weekday {
+            day && weekday
+        } else {
+            day || weekday
+        }
+    }
+
+    /// The first matching second of a day, at or after `from` seconds since midnight.
+    fn first_time_from(&self, from: u32) -> Option<u32> {
+        let (h0, m0, s0) = (from / 3600, from / 60 % 60, from % 60);
+
+        for h in (h0..24).filter(|&h| has(self.hours, h)) {
+            let m_start = if h == h0 { m0 } else { 0 };
+            for m in (m_start..60).filter(|&m| has(self.minutes, m)) {
+                let s_start = if h == h0 && m == m0 { s0 } else { 0 };
+                if let Some(s) = (s_start..60).find(|&s| has(self.seconds, s)) {
+                    return Some(h * 3600 + m * 60 + s);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = InvalidCron;
+
+    fn from_str(expr: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let (second, fields) = match fields.len() {
+            5 => ("0", &fields[..]),
+            6 => (fields[0], &fields[1..]),
+            _ => return Err(InvalidCron::new(expr, "expected 5 or 6 fields")),
+        };
+
+        let field = |field, min, max, names: &[&str]| {
+            parse_field(field, min, max, names).map_err(|reason| InvalidCron::new(expr, reason))
+        };
+
+        let mut weekdays = field(fields[4], 0, 7, &WEEKDAYS)?;
+        if has(weekdays, 7) {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+
+        Ok(CronSchedule {
+            expr: expr.to_string(),
+            seconds: field(second, 0, 59, &[])?,
+            minutes: field(fields[0], 0, 59, &[])?,
+            hours: field(fields[1], 0, 23, &[])?,
+            days: field(fields[2], 1, 31, &[])?,
+            months: field(fields[3], 1, 12, &MONTHS)?,
+            weekdays,
+            any_day: fields[2].starts_with(['*', '?']),
+            any_weekday: fields[4].starts_with(['*', '?']),
+            offset: 0,
+            missed: MissedFire::default(),
+        })
+    }
+}
+
+/// Parses a field into a bit set of the values it matches.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, &'static str> {
+    let value = |s: &str| {
+        s.parse::<u32>()
+            .ok()
+            .or_else(|| {
+                let i = names.iter().position(|name| name.eq_ignore_ascii_case(s))?;
+                Some(min + i as u32)
+            })
+            .filter(|v| (min..=max).contains(v))
+            .ok_or("value out of range")
+    };
+
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step.parse::<usize>().ok().filter(|&step| step > 0);
+                (range, Some(step.ok_or("invalid step")?))
+            }
+            None => (part, None),
+        };
+
+        let (start, end) = match range {
+            "*" | "?" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                // `a/n` starts at `a` and runs to the last value
+                None if step.is_some() => (value(range)?, max),
+                None => (value(range)?, value(range)?),
+            },
+        };
+
+        if start > end {
+            return Err("invalid range");
+        }
+        for v in (start..=end).step_by(step.unwrap_or(1)) {
+            set |= 1 << v;
+        }
+    }
+    Ok(set)
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// The month and day of the month of a day counted from 1 January 1970.
+fn month_day(days: i64) -> (u32, u32) {
+    // days from 1 March 0000, in a calendar starting with March
+    // so that leap days come last, see http://howardhinnant.github.io/date_algorithms.html
+    let days = days + 719_468;
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    (month as u32, day as u32)
+}
+
+fn from_unix(secs: i64) -> SystemTime {
+    if secs >= 0 {
+        UNIX_EPOCH + Duration::from_secs(secs as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+    }
+}
+
+pub struct InvalidCron {
+    pub expr: String,
+    pub reason: &'static str,
+}
+
+impl InvalidCron {
+    fn new(expr: &str, reason: &'static str) -> Self {
+        InvalidCron {
+            expr: expr.to_string(),
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for InvalidCron {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&format!(
+            "\"{}\". Invalid cron expression: {}",
+            self.expr, self.reason
+        ))
+    }
+}
+
+impl fmt::Debug for InvalidCron {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string())
+    }
+}
@@ -116,10 +116,11 @@ impl Actor for DeadLetterLogger {
     fn recv(&mut self, _: &Context<Self::Msg>, msg: Self::Msg, _: Option<BasicActorRef>) {
         slog::info!(
             self.logger,
-            "DeadLetter: {:?} => {:?} ({:?})",
+            "DeadLetter: {:?} => {:?} ({:?}, {:?})",
             msg.sender,
             msg.recipient,
-            msg.msg
+            msg.msg,
+            msg.reason
         )
     }
 }
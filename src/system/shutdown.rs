@@ -0,0 +1,239 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::{
+    sync::mpsc,
+    time::{timeout_at, Instant},
+};
+
+use crate::{
+    actor::{ActorPath, ActorRefFactory, ActorReference, BasicActorRef, BoxFuture},
+    system::{timer::Job, ActorSystem},
+};
+
+/// The time a phase is given to complete, unless set with `CoordinatedShutdown::set_timeout`.
+pub const DEFAULT_PHASE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A phase of the coordinated shutdown of an actor system.
+///
+/// Phases run one after the other, in the order of `ShutdownPhase::ALL`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShutdownPhase {
+    /// Stop accepting new work, such as closing listeners
+    StopAcceptingWork,
+
+    /// Complete the work in progress
+    Drain,
+
+    /// Stop the actors under `/user`
+    StopUserActors,
+
+    /// Stop the actors under `/system` and `/temp`
+    StopSystemActors,
+}
+
+impl ShutdownPhase {
+    pub const ALL: [ShutdownPhase; 4] = [
+        ShutdownPhase::StopAcceptingWork,
+        ShutdownPhase::Drain,
+        ShutdownPhase::StopUserActors,
+        ShutdownPhase::StopSystemActors,
+    ];
+}
+
+/// The outcome of a coordinated shutdown.
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownReport {
+    /// Tasks that had not completed when their phase timed out, with their phase
+    pub timed_out_tasks: Vec<(ShutdownPhase, String)>,
+
+    /// Actors that had not stopped when their phase timed out
+    pub unstopped_actors: Vec<ActorPath>,
+}
+
+impl ShutdownReport {
+    /// Whether every task completed and every actor stopped in time.
+    pub fn is_complete(&self) -> bool {
+        self.timed_out_tasks.is_empty() && self.unstopped_actors.is_empty()
+    }
+}
+
+type ShutdownTask = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+/// The tasks and timeouts of the coordinated shutdown of an actor system,
+/// run by `ActorSystem::shutdown`.
+///
+/// The tasks of a phase run concurrently, before the phase stops its actors.
+/// A phase ends when its tasks have completed and its actors have stopped,
+/// or when its timeout elapses, in which case the next phase starts while
+/// the remaining tasks keep running.
+///
+/// # Examples
+///
+/// ```
+/// # use tezedge_actor_system::actors::*;
+/// # use std::time::Duration;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let backend = tokio::runtime::Handle::current().into();
+/// let sys = ActorSystem::new(backend).unwrap();
+///
+/// let shutdown = sys.coordinated_shutdown();
+/// shutdown.add_task(ShutdownPhase::StopAcceptingWork, "close-listener", || async {
+///     println!("listener closed");
+/// });
+/// shutdown.set_timeout(ShutdownPhase::StopUserActors, Duration::from_secs(1));
+///
+/// let report = sys.shutdown().await;
+/// assert!(report.is_complete());
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct CoordinatedShutdown {
+    inner: Arc<Mutex<ShutdownState>>,
+}
+
+#[derive(Default)]
+struct ShutdownState {
+    tasks: HashMap<ShutdownPhase, Vec<(String, ShutdownTask)>>,
+    timeouts: HashMap<ShutdownPhase, Duration>,
+}
+
+impl CoordinatedShutdown {
+    /// Registers `task` to run during `phase`.
+    ///
+    /// `name` identifies the task in the `ShutdownReport` if it times out.
+    pub fn add_task<F, Fut>(&self, phase: ShutdownPhase, name: &str, task: F)
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let task: ShutdownTask = Box::new(move || Box::pin(task()));
+        self.inner
+            .lock()
+            .unwrap()
+            .tasks
+            .entry(phase)
+            .or_default()
+            .push((name.to_string(), task));
+    }
+
+    /// Sets the time `phase` is given to complete.
+    pub fn set_timeout(&self, phase: ShutdownPhase, timeout: Duration) {
+        self.inner.lock().unwrap().timeouts.insert(phase, timeout);
+    }
+
+    /// The time `phase` is given to complete.
+    pub fn timeout(&self, phase: ShutdownPhase) -> Duration {
+        self.inner
+            .lock()
+            .unwrap()
+            .timeouts
+            .get(&phase)
+            .copied()
+            .unwrap_or(DEFAULT_PHASE_TIMEOUT)
+    }
+
+    pub(crate) fn run(
+        &self,
+        sys: ActorSystem,
+    ) -> Pin<Box<dyn Future<Output = ShutdownReport> + Send>> {
+        let shutdown = self.clone();
+        Box::pin(async move {
+            let mut report = ShutdownReport::default();
+
+            for phase in ShutdownPhase::ALL {
+                let deadline = Instant::now() + shutdown.timeout(phase);
+                let tasks = shutdown
+                    .inner
+                    .lock()
+                    .unwrap()
+                    .tasks
+                    .remove(&phase)
+                    .unwrap_or_default();
+
+                for name in run_tasks(&sys, tasks, deadline).await {
+                    slog::warn!(sys.log(), "Shutdown task {} timed out in {:?}", name, phase);
+                    report.timed_out_tasks.push((phase, name));
+                }
+
+                let guardians = match phase {
+                    ShutdownPhase::StopUserActors => vec![sys.user_root().clone()],
+                    ShutdownPhase::StopSystemActors => {
+                        vec![sys.sys_root().clone(), sys.temp_root().clone()]
+                    }
+                    _ => continue,
+                };
+
+                for guardian in &guardians {
+                    sys.stop(guardian);
+                }
+                for guardian in &guardians {
+                    if timeout_at(deadline, guardian.cell.stopped()).await.is_err() {
+                        let unstopped = descendants(guardian);
+                        slog::warn!(
+                            sys.log(),
+                            "{} actors under {} did not stop in {:?}",
+                            unstopped.len(),
+                            guardian.path(),
+                            phase
+                        );
+                        report.unstopped_actors.extend(unstopped);
+                    }
+                }
+            }
+
+            // the root and the kernels of the remaining actors terminate
+            // once the actors that did not stop in time have stopped
+            sys.stop(sys.root());
+            let _ = sys.timer.lock().unwrap().send(Job::Stop);
+
+            report
+        })
+    }
+}
+
+/// Runs `tasks` concurrently until `deadline`, returning the names of those that did not complete.
+async fn run_tasks(
+    sys: &ActorSystem,
+    tasks: Vec<(String, ShutdownTask)>,
+    deadline: Instant,
+) -> Vec<String> {
+    let (done_tx, mut done_rx) = mpsc::unbounded_channel();
+    let mut pending: Vec<Option<String>> = Vec::with_capacity(tasks.len());
+
+    for (i, (name, task)) in tasks.into_iter().enumerate() {
+        let done_tx = done_tx.clone();
+        sys.backend.spawn(async move {
+            task().await;
+            let _ = done_tx.send(i);
+        });
+        pending.push(Some(name));
+    }
+    drop(done_tx);
+
+    let _ = timeout_at(deadline, async {
+        while let Some(i) = done_rx.recv().await {
+            pending[i] = None;
+        }
+    })
+    .await;
+
+    pending.into_iter().flatten().collect()
+}
+
+/// The paths of all actors below `actor`.
+fn descendants(actor: &BasicActorRef) -> Vec<ActorPath> {
+    let mut paths = Vec::new();
+    for child in actor.children() {
+        paths.push(child.path().clone());
+        paths.extend(descendants(&child));
+    }
+    paths
+}
@@ -1,21 +1,55 @@
 use std::{
-    sync::mpsc,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    convert::TryFrom,
+    fmt, iter,
+    sync::{
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex,
+    },
     thread,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
+use tokio::{sync::mpsc as tokio_mpsc, time};
 use uuid::Uuid;
 
 use crate::{
     actor::{ActorRef, BasicActorRef, Sender},
+    system::cron::{CronSchedule, MissedFire},
+    tokio_backend::ActorSystemBackendTokio,
+    util::random_u64,
     AnyMessage, Config, Message,
 };
 
-pub type TimerRef = mpsc::Sender<Job>;
+/// The handle used to send jobs to the timer of an actor system.
+#[derive(Clone)]
+pub enum TimerRef {
+    /// A `BasicTimer` running on its own thread
+    Thread(mpsc::Sender<Job>),
+    /// A `TokioTimer` running on the system's runtime
+    Tokio(tokio_mpsc::UnboundedSender<Job>),
+    /// A `VirtualClock`, firing jobs when it is advanced
+    Virtual(VirtualClock),
+}
+
+impl TimerRef {
+    /// Sends `job` to the timer, failing if the timer has stopped.
+    pub fn send(&self, job: Job) -> Result<(), mpsc::SendError<Job>> {
+        match self {
+            TimerRef::Thread(tx) => tx.send(job),
+            TimerRef::Tokio(tx) => tx.send(job).map_err(|e| mpsc::SendError(e.0)),
+            TimerRef::Virtual(clock) => clock.send(job),
+        }
+    }
+}
 
 pub type ScheduleId = Uuid;
 
 pub trait Timer {
+    /// Sends `msg` to `receiver` after `initial_delay`, then at a fixed rate of one message every `interval`.
+    ///
+    /// The same as `schedule_with` and `RepeatSchedule::fixed_rate`.
     fn schedule<T, M>(
         &self,
         initial_delay: Duration,
@@ -23,7 +57,23 @@ pub trait Timer {
         receiver: ActorRef<M>,
         sender: Sender,
         msg: T,
-    ) -> ScheduleId
+    ) -> ScheduleHandle
+    where
+        T: Message + Into<M>,
+        M: Message,
+    {
+        let schedule = RepeatSchedule::fixed_rate(initial_delay, interval);
+        self.schedule_with(schedule, receiver, sender, msg)
+    }
+
+    /// Sends `msg` to `receiver` repeatedly, following `schedule`.
+    fn schedule_with<T, M>(
+        &self,
+        schedule: RepeatSchedule,
+        receiver: ActorRef<M>,
+        sender: Sender,
+        msg: T,
+    ) -> ScheduleHandle
     where
         T: Message + Into<M>,
         M: Message;
@@ -34,18 +84,192 @@ pub trait Timer {
         receiver: ActorRef<M>,
         sender: Sender,
         msg: T,
-    ) -> ScheduleId
+    ) -> ScheduleHandle
+    where
+        T: Message + Into<M>,
+        M: Message;
+
+    /// Sends `msg` to `receiver` at each fire time of `schedule`.
+    fn schedule_cron<T, M>(
+        &self,
+        schedule: CronSchedule,
+        receiver: ActorRef<M>,
+        sender: Sender,
+        msg: T,
+    ) -> ScheduleHandle
     where
         T: Message + Into<M>,
         M: Message;
 
-    fn cancel_schedule(&self, id: Uuid);
+    fn cancel_schedule(&self, id: ScheduleId);
+}
+
+/// A scheduled message, cancelled when its handle is dropped.
+///
+/// `detach` keeps the schedule running without the handle,
+/// leaving it to be cancelled by id with `Timer::cancel_schedule`.
+#[must_use = "the schedule is cancelled when its handle is dropped, unless it is detached"]
+pub struct ScheduleHandle {
+    id: ScheduleId,
+    timer: Option<Arc<Mutex<TimerRef>>>,
+    owner: Option<BasicActorRef>,
+}
+
+impl ScheduleHandle {
+    pub(crate) fn new(
+        id: ScheduleId,
+        timer: Arc<Mutex<TimerRef>>,
+        owner: Option<BasicActorRef>,
+    ) -> Self {
+        ScheduleHandle {
+            id,
+            timer: Some(timer),
+            owner,
+        }
+    }
+
+    pub fn id(&self) -> ScheduleId {
+        self.id
+    }
+
+    /// Cancels the schedule, as dropping the handle does.
+    pub fn cancel(self) {}
+
+    /// Keeps the schedule running once the handle is dropped, returning its id.
+    pub fn detach(mut self) -> ScheduleId {
+        self.timer = None;
+        self.id
+    }
+}
+
+impl Drop for ScheduleHandle {
+    fn drop(&mut self) {
+        if let Some(timer) = self.timer.take() {
+            release(self.owner.take(), &self.id);
+            let _ = timer.lock().unwrap().send(Job::Cancel(self.id));
+        }
+    }
+}
+
+impl fmt::Debug for ScheduleHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ScheduleHandle[{}]", self.id)
+    }
+}
+
+/// How a repeating schedule places its messages in time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RepeatMode {
+    /// Messages are sent at `initial_delay + n * interval` from the time of scheduling.
+    /// Messages that are late, because the system was busy or suspended,
+    /// are sent right away to catch up.
+    FixedRate,
+
+    /// Each message is sent `interval` after the previous one was sent,
+    /// so that delays accumulate.
+    FixedDelay,
+}
+
+/// A repeating schedule, used with `Timer::schedule_with`.
+///
+/// # Examples
+///
+/// ```
+/// # use tezedge_actor_system::actors::*;
+/// # use std::time::Duration;
+///
+/// // every 10 seconds, give or take a second, at most 6 times
+/// let schedule = RepeatSchedule::fixed_rate(Duration::ZERO, Duration::from_secs(10))
+///     .with_jitter(Duration::from_secs(1))
+///     .with_max_fires(6);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RepeatSchedule {
+    initial_delay: Duration,
+    interval: Duration,
+    mode: RepeatMode,
+    jitter: Duration,
+    max_fires: Option<u64>,
+}
+
+impl RepeatSchedule {
+    pub fn fixed_rate(initial_delay: Duration, interval: Duration) -> Self {
+        RepeatSchedule::new(initial_delay, interval, RepeatMode::FixedRate)
+    }
+
+    pub fn fixed_delay(initial_delay: Duration, interval: Duration) -> Self {
+        RepeatSchedule::new(initial_delay, interval, RepeatMode::FixedDelay)
+    }
+
+    fn new(initial_delay: Duration, interval: Duration, mode: RepeatMode) -> Self {
+        RepeatSchedule {
+            initial_delay,
+            interval,
+            mode,
+            jitter: Duration::ZERO,
+            max_fires: None,
+        }
+    }
+
+    /// Delays each message by a random duration of up to `jitter`.
+    ///
+    /// At a fixed rate, the jitter of a message doesn't delay the following ones.
+    pub fn with_jitter(self, jitter: Duration) -> Self {
+        RepeatSchedule { jitter, ..self }
+    }
+
+    /// Ends the schedule once `count` messages were sent.
+    pub fn with_max_fires(self, count: u64) -> Self {
+        RepeatSchedule {
+            max_fires: Some(count),
+            ..self
+        }
+    }
+
+    pub fn mode(&self) -> RepeatMode {
+        self.mode
+    }
+
+    pub(crate) fn into_job(
+        self,
+        id: ScheduleId,
+        receiver: BasicActorRef,
+        sender: Sender,
+        msg: AnyMessage,
+        owner: Option<BasicActorRef>,
+    ) -> RepeatJob {
+        RepeatJob {
+            id,
+            send_at: Instant::now() + self.initial_delay,
+            interval: self.interval,
+            mode: self.mode,
+            jitter: self.jitter,
+            remaining: self.max_fires,
+            receiver,
+            sender,
+            msg,
+            owner,
+        }
+    }
 }
 
 pub enum Job {
     Once(OnceJob),
-    Repeat(RepeatJob),
+    Repeat(Box<RepeatJob>),
+    Cron(Box<CronJob>),
     Cancel(Uuid),
+    /// Stops the timer, dropping the scheduled jobs
+    Stop,
+}
+
+/// A timer started by an actor through its `Context`.
+///
+/// These timers are cancelled when the actor stops or restarts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ActiveTimer {
+    pub id: ScheduleId,
+    /// The key of a timer started with `Context::start_timer` or `Context::start_repeat_timer`
+    pub key: Option<String>,
 }
 
 pub struct OnceJob {
@@ -54,140 +278,588 @@ pub struct OnceJob {
     pub receiver: BasicActorRef,
     pub sender: Sender,
     pub msg: AnyMessage,
+    /// The actor that started the job, if it was started through a `Context`
+    pub owner: Option<BasicActorRef>,
 }
 
 impl OnceJob {
     pub fn send(mut self) {
-        let _ = self.receiver.try_tell_any(&mut self.msg, self.sender);
+        let owner = self.owner.as_ref();
+        send_msg(&self.id, &self.receiver, owner, &mut self.msg, self.sender);
+        release(self.owner, &self.id);
     }
 }
 
 pub struct RepeatJob {
     pub id: Uuid,
+    /// The time of the next message, before jitter
     pub send_at: Instant,
     pub interval: Duration,
+    pub mode: RepeatMode,
+    pub jitter: Duration,
+    /// The number of messages left to send, if limited
+    pub remaining: Option<u64>,
     pub receiver: BasicActorRef,
     pub sender: Sender,
     pub msg: AnyMessage,
+    /// The actor that started the job, if it was started through a `Context`
+    pub owner: Option<BasicActorRef>,
 }
 
 impl RepeatJob {
     pub fn send(&mut self) {
-        let _ = self
-            .receiver
-            .try_tell_any(&mut self.msg, self.sender.clone());
+        let owner = self.owner.as_ref();
+        send_msg(
+            &self.id,
+            &self.receiver,
+            owner,
+            &mut self.msg,
+            self.sender.clone(),
+        );
+    }
+}
+
+pub struct CronJob {
+    pub id: Uuid,
+    pub schedule: CronSchedule,
+    pub receiver: BasicActorRef,
+    pub sender: Sender,
+    pub msg: AnyMessage,
+    /// The actor that started the job, if it was started through a `Context`
+    pub owner: Option<BasicActorRef>,
+}
+
+impl CronJob {
+    pub fn send(&mut self) {
+        let owner = self.owner.as_ref();
+        send_msg(
+            &self.id,
+            &self.receiver,
+            owner,
+            &mut self.msg,
+            self.sender.clone(),
+        );
+    }
+
+    /// Sends the messages due from fire time `next` up to `now`, following the
+    /// missed fire policy, and returns the fire time after `now`.
+    fn fire(&mut self, next: SystemTime, now: SystemTime) -> Option<SystemTime> {
+        if next > now {
+            // the clock moved back
+            return Some(next);
+        }
+
+        match self.schedule.missed_fire() {
+            MissedFire::FireAll => {
+                let mut next = Some(next);
+                while let Some(at) = next.filter(|at| *at <= now) {
+                    self.send();
+                    next = self.schedule.next_after(at);
+                }
+                next
+            }
+            missed => {
+                let skip = missed == MissedFire::Skip
+                    && matches!(self.schedule.next_after(next), Some(at) if at <= now);
+                if !skip {
+                    self.send();
+                }
+                self.schedule.next_after(now)
+            }
+        }
+    }
+}
+
+/// A random duration of up to `max`.
+fn random_delay(max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    let random = random_u64();
+    let max = u64::try_from(max.as_nanos()).unwrap_or(u64::MAX);
+    // up to `max` inclusive, any value if that is all of them
+    Duration::from_nanos(max.checked_add(1).map_or(random, |range| random % range))
+}
+
+/// Sends the message of the job `id`. The message of a timer an actor started
+/// for itself is dropped if the timer is cancelled or replaced before it is received.
+fn send_msg(
+    id: &Uuid,
+    receiver: &BasicActorRef,
+    owner: Option<&BasicActorRef>,
+    msg: &mut AnyMessage,
+    sender: Sender,
+) {
+    let _ = match owner {
+        Some(owner) if owner.cell.is_same(&receiver.cell) => {
+            receiver.cell.send_timer_msg(id, msg, sender)
+        }
+        _ => receiver.try_tell_any(msg, sender),
+    };
+}
+
+/// Removes the job `id` from the timers of `owner`, once it no longer fires.
+fn release(owner: Option<BasicActorRef>, id: &Uuid) {
+    if let Some(owner) = owner {
+        owner.cell.remove_timer(id);
     }
 }
 
 // Default timer implementation
 
+/// A scheduled job, in `BasicTimer::jobs`.
+enum Scheduled {
+    Once(OnceJob),
+    Repeat(RepeatJob),
+    Cron { job: CronJob, next: SystemTime },
+}
+
+/// The default timer, running on its own thread.
+///
+/// Jobs are kept in a binary heap ordered by deadline, with a map from
+/// their id for constant time cancellation. The thread sleeps until the
+/// earliest deadline or until a command arrives, and handles all pending
+/// commands on each wake-up.
+///
+/// Each heap entry carries a sequence number, so that the entries of cancelled
+/// and rescheduled jobs are skipped. They are removed from the heap when their
+/// deadline is reached, or when the heap is compacted because most of its
+/// entries are stale.
+#[derive(Default)]
 pub struct BasicTimer {
-    once_jobs: Vec<OnceJob>,
-    repeat_jobs: Vec<RepeatJob>,
+    // the jobs by id, with the sequence number of their heap entry
+    jobs: HashMap<Uuid, (u64, Scheduled)>,
+    deadlines: BinaryHeap<Reverse<(Instant, u64, Uuid)>>,
+    // breaks ties between jobs with the same deadline in scheduling order
+    seq: u64,
+    // the instant at which the wall clock of cron schedules reads the given time,
+    // if it doesn't follow the system clock
+    epoch: Option<(Instant, SystemTime)>,
 }
 
 impl BasicTimer {
-    pub fn start(cfg: &Config) -> TimerRef {
-        let cfg = cfg.scheduler.clone();
-
-        let mut process = BasicTimer {
-            once_jobs: Vec::new(),
-            repeat_jobs: Vec::new(),
-        };
+    pub fn start(_cfg: &Config) -> TimerRef {
+        let mut process = BasicTimer::default();
 
         let (tx, rx) = mpsc::channel();
-        thread::spawn(move || loop {
-            process.execute_once_jobs();
-            process.execute_repeat_jobs();
-
-            if let Ok(job) = rx.try_recv() {
-                match job {
-                    Job::Cancel(id) => process.cancel(&id),
-                    Job::Once(job) => process.schedule_once(job),
-                    Job::Repeat(job) => process.schedule_repeat(job),
+        thread::Builder::new()
+            .name("actor-timer".to_string())
+            .spawn(move || loop {
+                let received = match process.next_deadline() {
+                    Some(at) => rx.recv_timeout(at.saturating_duration_since(Instant::now())),
+                    None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                };
+
+                match received {
+                    Ok(job) => {
+                        // along with the commands sent in the meantime
+                        let mut jobs = iter::once(job).chain(rx.try_iter());
+                        if !jobs.all(|job| process.handle(job, Instant::now())) {
+                            break;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => break,
                 }
+
+                process.execute_due(Instant::now());
+            })
+            .unwrap();
+
+        TimerRef::Thread(tx)
+    }
+
+    /// Applies a command, returning `false` if the timer should stop.
+    fn handle(&mut self, job: Job, now: Instant) -> bool {
+        match job {
+            Job::Once(job) => self.schedule_once(job, now),
+            Job::Repeat(job) => self.schedule_repeat(*job, now),
+            Job::Cron(job) => self.schedule_cron(*job, now),
+            Job::Cancel(id) => self.cancel(&id),
+            Job::Stop => return false,
+        }
+        true
+    }
+
+    /// The earliest deadline of the scheduled jobs, if any.
+    pub fn next_deadline(&mut self) -> Option<Instant> {
+        while let Some(&Reverse((at, seq, id))) = self.deadlines.peek() {
+            if self.is_current(seq, &id) {
+                return Some(at);
             }
+            self.deadlines.pop();
+        }
+        None
+    }
 
-            thread::sleep(Duration::from_millis(cfg.frequency_millis));
-        });
+    /// Sends the messages of the jobs whose deadline is at or before `now`.
+    pub fn execute_due(&mut self, now: Instant) {
+        while let Some(&Reverse((at, seq, id))) = self.deadlines.peek() {
+            if at > now {
+                break;
+            }
+            self.deadlines.pop();
+            if !self.is_current(seq, &id) {
+                continue;
+            }
 
-        tx
+            match self.jobs.remove(&id).map(|(_, job)| job) {
+                Some(Scheduled::Once(job)) => job.send(),
+                Some(Scheduled::Repeat(job)) => self.fire_repeat(job, now),
+                // a cron job ends when its receiver stops
+                Some(Scheduled::Cron { job, .. }) if job.receiver.cell.is_stopped() => {
+                    release(job.owner, &job.id)
+                }
+                Some(Scheduled::Cron { mut job, next }) => {
+                    let time = self.wall_time(now);
+                    match job.fire(next, time) {
+                        Some(next) => self.insert_cron(job, next, now),
+                        None => release(job.owner, &job.id),
+                    }
+                }
+                None => {}
+            }
+        }
     }
 
-    pub fn execute_once_jobs(&mut self) {
-        let (send, keep): (Vec<OnceJob>, Vec<OnceJob>) = self
-            .once_jobs
-            .drain(..)
-            .partition(|j| Instant::now() >= j.send_at);
+    pub fn cancel(&mut self, id: &Uuid) {
+        if self.jobs.remove(id).is_some() && self.deadlines.len() > 2 * self.jobs.len() + 1024 {
+            self.compact();
+        }
+    }
 
-        // send those messages where the 'send_at' time has been reached or elapsed
-        for job in send {
+    pub fn schedule_once(&mut self, job: OnceJob, now: Instant) {
+        if now >= job.send_at {
             job.send();
+        } else {
+            self.insert(job.id, job.send_at, Scheduled::Once(job));
         }
+    }
 
-        // for those messages that are not to be sent yet, just put them back on the vec
-        for job in keep {
-            self.once_jobs.push(job);
+    pub fn schedule_repeat(&mut self, job: RepeatJob, now: Instant) {
+        if now >= job.send_at {
+            self.fire_repeat(job, now);
+        } else {
+            let at = job.send_at + random_delay(job.jitter);
+            self.insert(job.id, at, Scheduled::Repeat(job));
         }
     }
 
-    pub fn execute_repeat_jobs(&mut self) {
-        for job in self.repeat_jobs.iter_mut() {
-            if Instant::now() >= job.send_at {
-                job.send_at = Instant::now() + job.interval;
-                job.send();
-            }
+    /// Sends the message of a repeating job and schedules the next one.
+    fn fire_repeat(&mut self, mut job: RepeatJob, now: Instant) {
+        // a repeating job ends when its receiver stops or it was sent enough times
+        if job.receiver.cell.is_stopped() || job.remaining == Some(0) {
+            return release(job.owner, &job.id);
+        }
+
+        job.send();
+        job.remaining = job.remaining.map(|remaining| remaining - 1);
+        if job.remaining == Some(0) {
+            return release(job.owner, &job.id);
         }
+
+        // a zero interval would fire forever without the clock moving
+        let interval = job.interval.max(Duration::from_nanos(1));
+        job.send_at = match job.mode {
+            RepeatMode::FixedRate => job.send_at + interval,
+            RepeatMode::FixedDelay => now + interval,
+        };
+        let at = job.send_at + random_delay(job.jitter);
+        self.insert(job.id, at, Scheduled::Repeat(job));
     }
 
-    pub fn cancel(&mut self, id: &Uuid) {
-        // slightly sub optimal way of canceling because we don't know the job type
-        // so need to do the remove on both vecs
+    pub fn schedule_cron(&mut self, job: CronJob, now: Instant) {
+        if let Some(next) = job.schedule.next_after(self.wall_time(now)) {
+            self.insert_cron(job, next, now);
+        }
+    }
 
-        if let Some(pos) = self.once_jobs.iter().position(|job| &job.id == id) {
-            self.once_jobs.remove(pos);
+    /// A timer whose cron schedules read `time` at `instant`, instead of following the system clock.
+    pub fn with_epoch(instant: Instant, time: SystemTime) -> Self {
+        BasicTimer {
+            epoch: Some((instant, time)),
+            ..BasicTimer::default()
         }
+    }
 
-        if let Some(pos) = self.repeat_jobs.iter().position(|job| &job.id == id) {
-            self.repeat_jobs.remove(pos);
+    /// The wall clock time at `now`.
+    pub fn wall_time(&self, now: Instant) -> SystemTime {
+        match self.epoch {
+            Some((instant, time)) => time + now.saturating_duration_since(instant),
+            None => SystemTime::now(),
         }
     }
 
-    pub fn schedule_once(&mut self, job: OnceJob) {
-        if Instant::now() >= job.send_at {
-            job.send();
-        } else {
-            self.once_jobs.push(job);
+    /// The number of scheduled jobs.
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Schedules `job` at `at`, replacing a job with the same id.
+    fn insert(&mut self, id: Uuid, at: Instant, job: Scheduled) {
+        self.seq += 1;
+        self.jobs.insert(id, (self.seq, job));
+        self.deadlines.push(Reverse((at, self.seq, id)));
+    }
+
+    fn insert_cron(&mut self, job: CronJob, next: SystemTime, now: Instant) {
+        let at = now + next.duration_since(self.wall_time(now)).unwrap_or_default();
+        self.insert(job.id, at, Scheduled::Cron { job, next });
+    }
+
+    /// Whether the heap entry `seq` is the current one of job `id`.
+    fn is_current(&self, seq: u64, id: &Uuid) -> bool {
+        matches!(self.jobs.get(id), Some((current, _)) if *current == seq)
+    }
+
+    /// Rebuilds the heap without its stale entries.
+    fn compact(&mut self) {
+        let jobs = &self.jobs;
+        self.deadlines.retain(
+            |Reverse((_, seq, id))| matches!(jobs.get(id), Some((current, _)) if current == seq),
+        );
+    }
+}
+
+/// A timer running as a task on the system's tokio runtime,
+/// selected with `backend = "tokio"` in the `[scheduler]` configuration.
+///
+/// Deadlines follow the tokio clock, so schedules are driven by
+/// `tokio::time::pause` and `tokio::time::advance` in tests.
+pub struct TokioTimer;
+
+impl TokioTimer {
+    pub fn start(backend: &ActorSystemBackendTokio) -> TimerRef {
+        let (tx, mut rx) = tokio_mpsc::unbounded_channel();
+
+        backend.spawn(async move {
+            let mut process =
+                BasicTimer::with_epoch(time::Instant::now().into_std(), SystemTime::now());
+            loop {
+                let received = match process.next_deadline() {
+                    Some(at) => time::timeout_at(at.into(), rx.recv()).await.ok(),
+                    None => Some(rx.recv().await),
+                };
+
+                match received {
+                    Some(Some(job)) => {
+                        let now = time::Instant::now().into_std();
+                        // along with the commands sent in the meantime
+                        let mut jobs = iter::once(job).chain(iter::from_fn(|| rx.try_recv().ok()));
+                        if !jobs.all(|job| process.handle(rebase(job, now), now)) {
+                            break;
+                        }
+                    }
+                    Some(None) => break,
+                    None => {}
+                }
+
+                process.execute_due(time::Instant::now().into_std());
+            }
+        });
+
+        TimerRef::Tokio(tx)
+    }
+}
+
+/// Moves the deadline of `job`, set from the system clock, to a clock reading `now`.
+fn rebase(mut job: Job, now: Instant) -> Job {
+    let send_at = match &mut job {
+        Job::Once(job) => &mut job.send_at,
+        Job::Repeat(job) => &mut job.send_at,
+        Job::Cron(_) | Job::Cancel(_) | Job::Stop => return job,
+    };
+    *send_at = now + send_at.saturating_duration_since(Instant::now());
+    job
+}
+
+/// A timer driven by a manually advanced clock, for testing time-dependent actors.
+///
+/// Scheduled jobs fire synchronously, in deadline order, when the clock is
+/// advanced past their deadline, regardless of the time actually elapsed.
+/// A repeating job fires once for each interval the clock is advanced by.
+///
+/// # Examples
+///
+/// ```
+/// # use tezedge_actor_system::actors::*;
+/// # use std::time::Duration;
+///
+/// #[derive(Default)]
+/// struct Alarm;
+///
+/// impl Actor for Alarm {
+///     type Msg = String;
+///
+///     fn recv(&mut self, _ctx: &Context<Self::Msg>, msg: Self::Msg, _sender: Sender) {
+///         println!("{}", msg);
+///     }
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let clock = VirtualClock::new();
+/// let sys = SystemBuilder::new()
+///     .exec(tokio::runtime::Handle::current().into())
+///     .timer(clock.timer())
+///     .create()
+///     .unwrap();
+///
+/// let alarm = sys.actor_of::<Alarm>("alarm").unwrap();
+/// sys.schedule_once(Duration::from_secs(3600), alarm, None, "wake up".to_string())
+///     .detach();
+/// assert_eq!(clock.scheduled(), 1);
+///
+/// // the message is sent right away
+/// clock.advance(Duration::from_secs(3600));
+/// assert_eq!(clock.scheduled(), 0);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct VirtualClock {
+    inner: Arc<Mutex<VirtualClockState>>,
+}
+
+struct VirtualClockState {
+    start: Instant,
+    now: Instant,
+    timer: BasicTimer,
+    stopped: bool,
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        VirtualClock::new()
+    }
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        VirtualClock::starting_at(SystemTime::now())
+    }
+
+    /// A clock whose wall clock time, used by cron schedules, starts at `time`.
+    pub fn starting_at(time: SystemTime) -> Self {
+        let now = Instant::now();
+        let state = VirtualClockState {
+            start: now,
+            now,
+            timer: BasicTimer::with_epoch(now, time),
+            stopped: false,
+        };
+
+        VirtualClock {
+            inner: Arc::new(Mutex::new(state)),
         }
     }
 
-    pub fn schedule_repeat(&mut self, mut job: RepeatJob) {
-        if Instant::now() >= job.send_at {
-            job.send();
+    /// The timer reference to create an actor system with, using `SystemBuilder::timer`.
+    pub fn timer(&self) -> TimerRef {
+        TimerRef::Virtual(self.clone())
+    }
+
+    /// Moves the clock forward by `duration`, sending the messages of the jobs
+    /// that become due.
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.inner.lock().unwrap();
+        let target = state.now + duration;
+
+        while let Some(at) = state.timer.next_deadline() {
+            if at > target {
+                break;
+            }
+            state.now = at;
+            state.timer.execute_due(at);
         }
-        self.repeat_jobs.push(job);
+        state.now = target;
+    }
+
+    /// Sets the wall clock time without moving the clock or firing any job,
+    /// as when the system is suspended or its clock is adjusted.
+    ///
+    /// Cron schedules see the fire times they missed the next time they fire.
+    pub fn jump_to(&self, time: SystemTime) {
+        let mut state = self.inner.lock().unwrap();
+        let now = state.now;
+        state.timer.epoch = Some((now, time));
+    }
+
+    /// The wall clock time of the clock.
+    pub fn time(&self) -> SystemTime {
+        let state = self.inner.lock().unwrap();
+        state.timer.wall_time(state.now)
+    }
+
+    /// The time the clock has been advanced by since it was created.
+    pub fn elapsed(&self) -> Duration {
+        let state = self.inner.lock().unwrap();
+        state.now - state.start
     }
+
+    /// The number of scheduled jobs.
+    pub fn scheduled(&self) -> usize {
+        self.inner.lock().unwrap().timer.len()
+    }
+
+    fn send(&self, job: Job) -> Result<(), mpsc::SendError<Job>> {
+        let mut state = self.inner.lock().unwrap();
+        if state.stopped {
+            return Err(mpsc::SendError(job));
+        }
+
+        let now = state.now;
+        if !state.timer.handle(rebase(job, now), now) {
+            state.stopped = true;
+            state.timer = BasicTimer::with_epoch(now, state.timer.wall_time(now));
+        }
+        Ok(())
+    }
+}
+
+/// The implementation of the timer of an actor system.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimerBackend {
+    /// `BasicTimer`, running on a dedicated thread
+    #[default]
+    Thread,
+    /// `TokioTimer`, running on the system's tokio runtime
+    Tokio,
 }
 
 #[derive(Clone, Debug)]
 pub struct BasicTimerConfig {
+    #[deprecated(note = "unused, the timer sleeps until the next deadline")]
     pub frequency_millis: u64,
+    pub backend: TimerBackend,
 }
 
+#[allow(deprecated)]
 impl Default for BasicTimerConfig {
     fn default() -> Self {
         BasicTimerConfig {
             frequency_millis: 50,
+            backend: TimerBackend::default(),
         }
     }
 }
 
 impl BasicTimerConfig {
     // Option<()> allow to use ? for parsing toml value, ignore it
+    #[allow(deprecated)]
     pub fn merge(&mut self, v: &toml::Value) -> Option<()> {
         let v = v.as_table()?;
-        let frequency_millis = v.get("frequency_millis")?.as_integer()? as _;
-        self.frequency_millis = frequency_millis;
+        if let Some(frequency_millis) = v.get("frequency_millis").and_then(|v| v.as_integer()) {
+            self.frequency_millis = frequency_millis as _;
+        }
+        match v.get("backend")?.as_str()? {
+            "thread" => self.backend = TimerBackend::Thread,
+            "tokio" => self.backend = TimerBackend::Tokio,
+            _ => {}
+        }
         None
     }
 }
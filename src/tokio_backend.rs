@@ -27,6 +27,15 @@ impl From<Handle> for ActorSystemBackendTokio {
     }
 }
 
+impl ActorSystemBackendTokio {
+    pub(crate) fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.handle.spawn(future);
+    }
+}
+
 impl ActorSystemBackend for ActorSystemBackendTokio {
     type Tx = SendingBackendTokio;
 
@@ -0,0 +1,7 @@
+use uuid::Uuid;
+
+/// A random number for jitter and random selection, taken from the random
+/// bits of a v4 `Uuid` to avoid another dependency.
+pub(crate) fn random_u64() -> u64 {
+    Uuid::new_v4().as_u128() as u64
+}
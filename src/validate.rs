@@ -1,8 +1,14 @@
 use std::fmt;
 
+/// Validates an actor name.
+///
+/// A leading `$` is allowed, as in the names generated for anonymous actors.
 pub fn validate_name(name: &str) -> Result<(), InvalidName> {
     let alphabet = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_-";
-    for c in name.chars() {
+    if name == "$" {
+        return Err(InvalidName { name: name.into() });
+    }
+    for c in name.strip_prefix('$').unwrap_or(name).chars() {
         if !alphabet.contains(c) {
             return Err(InvalidName { name: name.into() });
         }
@@ -11,6 +17,23 @@ pub fn validate_name(name: &str) -> Result<(), InvalidName> {
     Ok(())
 }
 
+/// Validates an actor selection path, such as `/user/*`, `../sibling` or `child-?`.
+///
+/// Each segment is `.`, `..`, or a name in which `*` matches any characters
+/// and `?` matches a single character.
+pub fn validate_path(path: &str) -> Result<(), InvalidPath> {
+    let alphabet = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_-*?$";
+    let valid = path.split('/').all(|segment| {
+        matches!(segment, "" | "." | "..") || segment.chars().all(|c| alphabet.contains(c))
+    });
+
+    if valid && !path.is_empty() {
+        Ok(())
+    } else {
+        Err(InvalidPath::selection(path))
+    }
+}
+
 pub struct InvalidName {
     pub name: String,
 }
@@ -29,3 +52,48 @@ impl fmt::Debug for InvalidName {
         f.write_str(&self.to_string())
     }
 }
+
+pub struct InvalidPath {
+    pub path: String,
+    selection: bool,
+}
+
+impl InvalidPath {
+    /// An invalid actor selection path
+    pub(crate) fn selection(path: &str) -> Self {
+        InvalidPath {
+            path: path.into(),
+            selection: true,
+        }
+    }
+
+    /// An invalid `ActorPath`
+    pub(crate) fn actor_path(path: &str) -> Self {
+        InvalidPath {
+            path: path.into(),
+            selection: false,
+        }
+    }
+}
+
+impl fmt::Display for InvalidPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.selection {
+            f.write_str(&format!(
+                "\"{}\". Invalid selection path. Segments must be ., .. or contain only a-Z, 0-9, _, -, *, ? or $",
+                self.path
+            ))
+        } else {
+            f.write_str(&format!(
+                "\"{}\". Invalid path. Names must contain only a-Z, 0-9, _, or -",
+                self.path
+            ))
+        }
+    }
+}
+
+impl fmt::Debug for InvalidPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string())
+    }
+}
@@ -175,3 +175,63 @@ async fn actor_stop() {
     system.stop(&parent);
     p_assert_eq!(listen, ());
 }
+
+#[derive(Default)]
+struct AnonParent {
+    probe: Option<ChannelProbe<(), String>>,
+}
+
+impl Actor for AnonParent {
+    type Msg = ChannelProbe<(), String>;
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, _sender: Sender) {
+        let child = ctx.actor_of_anon::<Child>().unwrap();
+        msg.event(child.path().to_string());
+        self.probe = Some(msg);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn actor_create_anon() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let a = sys.actor_of_anon::<Child>().unwrap();
+    let b = sys.actor_of_anon::<Child>().unwrap();
+    assert!(a.name().starts_with('$'));
+    assert!(b.name().starts_with('$'));
+    assert_ne!(a.path(), b.path());
+    assert_eq!(a.parent().path(), sys.user_root().path());
+
+    // generated names are reserved
+    assert!(sys.actor_of::<Child>(a.name()).is_err());
+    assert!(sys.actor_of::<Child>("$named").is_err());
+
+    let names: std::collections::HashSet<String> = (0..1000)
+        .map(|_| sys.actor_of_anon::<Child>().unwrap().name().to_string())
+        .collect();
+    assert_eq!(names.len(), 1000);
+
+    let parent = sys.actor_of::<AnonParent>("parent").unwrap();
+    let (probe, listen) = probe();
+    parent.tell(probe, None);
+    let child: ActorPath = listen.recv().parse().unwrap();
+    assert!(child.name().starts_with('$'));
+    assert_eq!(child.parent().as_ref(), Some(parent.path()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn actor_create_temp() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let temp = sys.temp_actor_of::<Child>().unwrap();
+    assert!(temp.path().is_descendant_of(sys.temp_root().path()));
+    assert!(temp.name().starts_with('$'));
+
+    let parent = sys.temp_actor_of::<AnonParent>().unwrap();
+    let (probe, listen) = probe();
+    parent.tell(probe, None);
+    let child: ActorPath = listen.recv().parse().unwrap();
+    assert!(child.is_descendant_of(sys.temp_root().path()));
+}
@@ -0,0 +1,125 @@
+use tezedge_actor_system::actors::*;
+
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct Ping(u32);
+
+#[derive(Clone, Debug)]
+pub struct Ignore;
+
+#[derive(Clone, Debug)]
+pub struct Stop;
+
+#[derive(Clone, Debug)]
+pub struct Block;
+
+#[actor(Ping, Ignore, Stop, Block)]
+#[derive(Default)]
+struct Responder;
+
+impl Actor for Responder {
+    type Msg = ResponderMsg;
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
+        self.receive(ctx, msg, sender);
+    }
+}
+
+impl Receive<Ping> for Responder {
+    type Msg = ResponderMsg;
+
+    fn receive(&mut self, ctx: &Context<Self::Msg>, msg: Ping, sender: Sender) {
+        sender
+            .unwrap()
+            .try_tell(msg.0 + 1, Some(ctx.myself().into()))
+            .unwrap();
+    }
+}
+
+impl Receive<Ignore> for Responder {
+    type Msg = ResponderMsg;
+
+    fn receive(&mut self, _ctx: &Context<Self::Msg>, _msg: Ignore, _sender: Sender) {}
+}
+
+impl Receive<Stop> for Responder {
+    type Msg = ResponderMsg;
+
+    fn receive(&mut self, ctx: &Context<Self::Msg>, _msg: Stop, _sender: Sender) {
+        ctx.stop(&ctx.myself);
+    }
+}
+
+impl Receive<Block> for Responder {
+    type Msg = ResponderMsg;
+
+    fn receive(&mut self, _ctx: &Context<Self::Msg>, _msg: Block, _sender: Sender) {
+        std::thread::sleep(Duration::from_millis(300));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ask_reply() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let actor = sys.actor_of::<Responder>("responder").unwrap();
+
+    for i in 0..100 {
+        let reply: u32 = actor.ask(Ping(i), Duration::from_secs(5)).await.unwrap();
+        assert_eq!(reply, i + 1);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ask_timeout() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let actor = sys.actor_of::<Responder>("responder").unwrap();
+
+    let reply = actor
+        .ask::<_, u32>(Ignore, Duration::from_millis(100))
+        .await;
+    assert!(matches!(reply, Err(AskError::Timeout)));
+
+    // the temporary actor is cleaned up after the timeout
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(!sys.temp_root().has_children());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ask_terminated() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let actor = sys.actor_of::<Responder>("responder").unwrap();
+
+    // the actor stops instead of replying
+    let reply = actor.ask::<_, u32>(Stop, Duration::from_secs(5)).await;
+    assert!(matches!(reply, Err(AskError::Terminated)));
+
+    // the actor is already gone
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let reply = actor.ask::<_, u32>(Ping(1), Duration::from_secs(5)).await;
+    assert!(matches!(reply, Err(AskError::Terminated)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn ask_mailbox_full() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let capacity = MailboxCapacity::bounded(1, OverflowPolicy::Reject);
+    let props = Props::with_mailbox(Props::new::<Responder>(), capacity);
+    let actor = sys.actor_of_props("responder", props).unwrap();
+
+    // the actor is blocked with a full mailbox
+    actor.tell(Block, None);
+    std::thread::sleep(Duration::from_millis(50));
+    actor.tell(Ignore, None);
+
+    let reply = actor.ask::<_, u32>(Ping(1), Duration::from_secs(5)).await;
+    assert!(matches!(reply, Err(AskError::MailboxFull)));
+}
@@ -0,0 +1,211 @@
+#[macro_use]
+extern crate riker_testkit;
+
+use tezedge_actor_system::actors::*;
+
+use riker_testkit::probe::channel::{probe, ChannelProbe};
+use riker_testkit::probe::{Probe, ProbeReceive};
+
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub enum SleeperMsg {
+    Sleep(u64),
+    Count,
+    Fail,
+}
+
+/// Records the start and end of each sleep, and the number of `Count` received
+struct Sleeper {
+    probe: ChannelProbe<(), String>,
+    count: u32,
+}
+
+impl AsyncActor for Sleeper {
+    type Msg = SleeperMsg;
+
+    fn recv<'a>(
+        &'a mut self,
+        _: &'a Context<Self::Msg>,
+        msg: Self::Msg,
+        _: Sender,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            match msg {
+                SleeperMsg::Sleep(millis) => {
+                    self.probe.event(format!("start {}", millis));
+                    tokio::time::sleep(Duration::from_millis(millis)).await;
+                    self.probe.event(format!("end {}", millis));
+                }
+                SleeperMsg::Count => {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    self.count += 1;
+                    self.probe.event(format!("count {}", self.count));
+                }
+                SleeperMsg::Fail => {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    panic!("// TEST PANIC // TEST PANIC // TEST PANIC //");
+                }
+            }
+        })
+    }
+}
+
+fn start_sleeper(sys: &ActorSystem, probe: ChannelProbe<(), String>) -> ActorRef<SleeperMsg> {
+    let props = Props::new_from_args(|probe| Async::new(Sleeper { probe, count: 0 }), probe);
+    sys.actor_of_props("sleeper", props).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn async_recv_in_order() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let actor = start_sleeper(&sys, probe);
+
+    actor.tell(SleeperMsg::Sleep(100), None);
+    actor.tell(SleeperMsg::Sleep(0), None);
+    actor.tell(SleeperMsg::Sleep(50), None);
+
+    // each message is received once the previous future has completed
+    for event in [
+        "start 100",
+        "end 100",
+        "start 0",
+        "end 0",
+        "start 50",
+        "end 50",
+    ] {
+        p_assert_eq!(listen, event.to_string());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn async_panic_restarts() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let actor = start_sleeper(&sys, probe);
+
+    actor.tell(SleeperMsg::Count, None);
+    actor.tell(SleeperMsg::Count, None);
+    p_assert_eq!(listen, "count 1".to_string());
+    p_assert_eq!(listen, "count 2".to_string());
+
+    // the restarted actor starts counting again
+    actor.tell(SleeperMsg::Fail, None);
+    actor.tell(SleeperMsg::Count, None);
+    p_assert_eq!(listen, "count 1".to_string());
+}
+
+#[derive(Default)]
+struct Panicker;
+
+impl Actor for Panicker {
+    type Msg = ();
+
+    fn recv(&mut self, _: &Context<Self::Msg>, _: Self::Msg, _: Sender) {
+        panic!("// TEST PANIC // TEST PANIC // TEST PANIC //");
+    }
+}
+
+/// Escalates the failure of its child while sleeping
+struct Escalator {
+    probe: ChannelProbe<(), String>,
+    child: Option<ActorRef<()>>,
+    count: u32,
+}
+
+impl AsyncActor for Escalator {
+    type Msg = SleeperMsg;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        self.child = Some(ctx.actor_of::<Panicker>("panicker").unwrap());
+    }
+
+    fn recv<'a>(
+        &'a mut self,
+        _: &'a Context<Self::Msg>,
+        msg: Self::Msg,
+        _: Sender,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            match msg {
+                SleeperMsg::Sleep(millis) => {
+                    self.child.as_ref().unwrap().tell((), None);
+                    tokio::time::sleep(Duration::from_millis(millis)).await;
+                    self.probe.event(format!("end {}", millis));
+                }
+                SleeperMsg::Count => {
+                    self.count += 1;
+                    self.probe.event(format!("count {}", self.count));
+                }
+                SleeperMsg::Fail => unreachable!(),
+            }
+        })
+    }
+
+    fn supervisor_strategy(&self) -> SupervisorStrategy {
+        SupervisorStrategy::one_for_one(Strategy::Escalate)
+    }
+}
+
+/// Resumes the `Escalator` it starts
+struct ResumeSup {
+    probe: Option<ChannelProbe<(), String>>,
+    child: Option<ActorRef<SleeperMsg>>,
+}
+
+impl Actor for ResumeSup {
+    type Msg = SleeperMsg;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        let probe = self.probe.take().unwrap();
+        let props = Props::new_from_args(
+            |probe| {
+                Async::new(Escalator {
+                    probe,
+                    child: None,
+                    count: 0,
+                })
+            },
+            probe,
+        );
+        self.child = Some(ctx.actor_of_props("escalator", props).unwrap());
+    }
+
+    fn recv(&mut self, _: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
+        self.child.as_ref().unwrap().tell(msg, sender);
+    }
+
+    fn supervisor_strategy(&self) -> SupervisorStrategy {
+        SupervisorStrategy::one_for_one(Strategy::Resume)
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn async_resumed_while_pending() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let props = Props::new_from_args(
+        |probe| ResumeSup {
+            probe: Some(probe),
+            child: None,
+        },
+        probe,
+    );
+    let sup = sys.actor_of_props("sup", props).unwrap();
+
+    // the escalator is resumed before its future completes,
+    // and receives the next messages once it has completed
+    sup.tell(SleeperMsg::Sleep(200), None);
+    sup.tell(SleeperMsg::Count, None);
+    sup.tell(SleeperMsg::Count, None);
+    p_assert_eq!(listen, "end 200".to_string());
+    p_assert_eq!(listen, "count 1".to_string());
+    p_assert_eq!(listen, "count 2".to_string());
+}
@@ -0,0 +1,168 @@
+#[macro_use]
+extern crate riker_testkit;
+
+use tezedge_actor_system::actors::*;
+
+use riker_testkit::probe::channel::{probe, ChannelProbe};
+use riker_testkit::probe::{Probe, ProbeReceive};
+
+#[derive(Clone, Debug)]
+pub enum SwitchMsg {
+    Ping,
+    Become,
+    Unbecome,
+    Fail,
+}
+
+/// Records which handler received each `Ping`
+struct Switch {
+    probe: ChannelProbe<(), String>,
+    pings: u32,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for Switch {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        Switch { probe, pings: 0 }
+    }
+}
+
+impl Switch {
+    fn handle(&mut self, handler: &str, ctx: &Context<SwitchMsg>, msg: SwitchMsg) {
+        match msg {
+            SwitchMsg::Ping => {
+                self.pings += 1;
+                self.probe.event(format!("{} {}", handler, self.pings));
+            }
+            SwitchMsg::Become if handler == "recv" => ctx.r#become(first),
+            SwitchMsg::Become => ctx.r#become(second),
+            SwitchMsg::Unbecome => ctx.unbecome(),
+            SwitchMsg::Fail => panic!("// TEST PANIC // TEST PANIC // TEST PANIC //"),
+        }
+    }
+}
+
+fn first(actor: &mut Switch, ctx: &Context<SwitchMsg>, msg: SwitchMsg, _: Sender) {
+    actor.handle("first", ctx, msg);
+}
+
+fn second(actor: &mut Switch, ctx: &Context<SwitchMsg>, msg: SwitchMsg, _: Sender) {
+    actor.handle("second", ctx, msg);
+}
+
+impl Actor for Switch {
+    type Msg = SwitchMsg;
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, _: Sender) {
+        self.handle("recv", ctx, msg);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn become_unbecome() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Switch, _>("switch", probe).unwrap();
+
+    actor.tell(SwitchMsg::Ping, None);
+    actor.tell(SwitchMsg::Become, None);
+    actor.tell(SwitchMsg::Ping, None);
+    actor.tell(SwitchMsg::Ping, None);
+    actor.tell(SwitchMsg::Unbecome, None);
+    actor.tell(SwitchMsg::Ping, None);
+
+    p_assert_eq!(listen, "recv 1".to_string());
+    p_assert_eq!(listen, "first 2".to_string());
+    p_assert_eq!(listen, "first 3".to_string());
+    p_assert_eq!(listen, "recv 4".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn become_stack() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Switch, _>("switch", probe).unwrap();
+
+    actor.tell(SwitchMsg::Become, None);
+    actor.tell(SwitchMsg::Become, None);
+    actor.tell(SwitchMsg::Ping, None);
+    actor.tell(SwitchMsg::Unbecome, None);
+    actor.tell(SwitchMsg::Ping, None);
+    actor.tell(SwitchMsg::Unbecome, None);
+    actor.tell(SwitchMsg::Ping, None);
+
+    p_assert_eq!(listen, "second 1".to_string());
+    p_assert_eq!(listen, "first 2".to_string());
+    p_assert_eq!(listen, "recv 3".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn become_reset_on_restart() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Switch, _>("switch", probe).unwrap();
+
+    actor.tell(SwitchMsg::Become, None);
+    actor.tell(SwitchMsg::Ping, None);
+    p_assert_eq!(listen, "first 1".to_string());
+
+    // the restarted actor receives messages in `recv` again
+    actor.tell(SwitchMsg::Fail, None);
+    actor.tell(SwitchMsg::Ping, None);
+    p_assert_eq!(listen, "recv 1".to_string());
+}
+
+/// Resumes a `Switch` child that fails, forwarding messages to it
+struct ResumeSup {
+    probe: Option<ChannelProbe<(), String>>,
+    child: Option<ActorRef<SwitchMsg>>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for ResumeSup {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        ResumeSup {
+            probe: Some(probe),
+            child: None,
+        }
+    }
+}
+
+impl Actor for ResumeSup {
+    type Msg = SwitchMsg;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        let probe = self.probe.take().unwrap();
+        self.child = Some(ctx.actor_of_args::<Switch, _>("switch", probe).unwrap());
+    }
+
+    fn supervisor_strategy(&self) -> SupervisorStrategy {
+        SupervisorStrategy::one_for_one(Strategy::Resume)
+    }
+
+    fn recv(&mut self, _: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
+        self.child.as_ref().unwrap().tell(msg, sender);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn become_kept_on_resume() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let sup = sys.actor_of_args::<ResumeSup, _>("sup", probe).unwrap();
+
+    sup.tell(SwitchMsg::Become, None);
+    sup.tell(SwitchMsg::Ping, None);
+    p_assert_eq!(listen, "first 1".to_string());
+
+    // the behavior panics, and the resumed actor keeps it
+    sup.tell(SwitchMsg::Fail, None);
+    sup.tell(SwitchMsg::Ping, None);
+    p_assert_eq!(listen, "first 2".to_string());
+}
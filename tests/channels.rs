@@ -351,3 +351,166 @@ async fn channel_dead_letters() {
 
     p_assert_eq!(listen, ());
 }
+
+// *** Terminated subscribers test ***
+/// Records the dead letters of the actor named "gone"
+struct GoneLetters {
+    probe: ChannelProbe<(), String>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for GoneLetters {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        GoneLetters { probe }
+    }
+}
+
+impl Actor for GoneLetters {
+    type Msg = DeadLetter;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        let sub = Box::new(ctx.myself());
+        ctx.system.dead_letters().tell(
+            Subscribe {
+                actor: sub,
+                topic: "*".into(),
+            },
+            None,
+        );
+    }
+
+    fn post_start(&mut self, _ctx: &Context<Self::Msg>) {
+        self.probe.event("started".to_string());
+    }
+
+    fn recv(&mut self, _ctx: &Context<Self::Msg>, msg: Self::Msg, _sender: Sender) {
+        if msg.recipient.name() == "gone" {
+            self.probe.event(msg.msg);
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn channel_unsubscribe_terminated() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (recorder, letters) = probe();
+    sys.actor_of_args::<GoneLetters, _>("gone-letters", recorder)
+        .unwrap();
+    letters.recv();
+
+    let chan: ChannelRef<SomeMessage> = channel("my-chan", &sys).unwrap();
+    let topic = Topic::from("my-topic");
+
+    let gone = sys
+        .actor_of_args::<Subscriber, _>("gone", (chan.clone(), topic.clone()))
+        .unwrap();
+    let live = sys
+        .actor_of_args::<Subscriber, _>("live", (chan.clone(), topic.clone()))
+        .unwrap();
+
+    let (probe, listen) = probe();
+    for sub in [&gone, &live] {
+        sub.tell(TestProbe(probe.clone()), None);
+        listen.recv();
+    }
+
+    sys.stop(&gone);
+    std::thread::sleep(std::time::Duration::from_secs(1));
+
+    // once "live" received the message, a dead letter for "gone" would have been published
+    chan.tell(
+        Publish {
+            msg: SomeMessage,
+            topic,
+        },
+        None,
+    );
+    listen.recv();
+
+    // the first dead letter of "gone" is the one sent directly
+    gone.tell(TestProbe(probe), None);
+    assert!(letters.recv().contains("TestProbe"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn channel_keep_resubscribed() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let chan: ChannelRef<SomeMessage> = channel("my-chan", &sys).unwrap();
+    let topic = Topic::from("my-topic");
+    let args = (chan.clone(), topic.clone());
+
+    let old = sys
+        .actor_of_args::<Subscriber, _>("sub-actor", args.clone())
+        .unwrap();
+    sys.stop(&old);
+
+    // a new actor at the same path subscribes, whether or not the channel
+    // was notified of the termination of the previous one yet
+    let new = loop {
+        match sys.actor_of_args::<Subscriber, _>("sub-actor", args.clone()) {
+            Ok(new) => break new,
+            Err(_) => tokio::time::sleep(std::time::Duration::from_millis(1)).await,
+        }
+    };
+
+    let (probe, listen) = probe();
+    new.tell(TestProbe(probe), None);
+    listen.recv();
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    chan.tell(
+        Publish {
+            msg: SomeMessage,
+            topic,
+        },
+        None,
+    );
+    p_assert_eq!(listen, ());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn channel_unsubscribe_previous_instance() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let chan: ChannelRef<SomeMessage> = channel("my-chan", &sys).unwrap();
+    let topic = Topic::from("my-topic");
+    let args = (chan.clone(), topic.clone());
+
+    let old = sys
+        .actor_of_args::<Subscriber, _>("sub-actor", args.clone())
+        .unwrap();
+    sys.stop(&old);
+
+    let new = loop {
+        match sys.actor_of_args::<Subscriber, _>("sub-actor", args.clone()) {
+            Ok(new) => break new,
+            Err(_) => tokio::time::sleep(std::time::Duration::from_millis(1)).await,
+        }
+    };
+
+    let (probe, listen) = probe();
+    new.tell(TestProbe(probe), None);
+    listen.recv();
+    std::thread::sleep(std::time::Duration::from_millis(100));
+
+    // unsubscribing the previous actor keeps the new one at the same path
+    chan.tell(
+        Unsubscribe {
+            actor: Box::new(old),
+            topic: topic.clone(),
+        },
+        None,
+    );
+    chan.tell(
+        Publish {
+            msg: SomeMessage,
+            topic,
+        },
+        None,
+    );
+    p_assert_eq!(listen, ());
+}
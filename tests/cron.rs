@@ -0,0 +1,95 @@
+use tezedge_actor_system::actors::*;
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+fn at(secs: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+fn next(expr: &str, after: u64) -> Option<SystemTime> {
+    expr.parse::<CronSchedule>().unwrap().next_after(at(after))
+}
+
+// 2024-02-28 23:59:00 UTC
+const FEB_28_2024: u64 = 1_709_164_740;
+// 2024-01-01 00:00:00 UTC, a Monday
+const JAN_1_2024: u64 = 1_704_067_200;
+
+#[test]
+fn cron_next_fire() {
+    assert_eq!(
+        next("*/15 * * * *", FEB_28_2024),
+        Some(at(FEB_28_2024 + 60))
+    );
+    assert_eq!(
+        next("0 0 * * *", FEB_28_2024 + 60),
+        Some(at(FEB_28_2024 + 60 + 86_400))
+    );
+
+    // seconds field
+    assert_eq!(next("*/10 * * * * *", 5), Some(at(10)));
+    assert_eq!(next("5,50 * * * * *", 10), Some(at(50)));
+
+    // leap days
+    assert_eq!(next("0 12 29 2 *", FEB_28_2024), Some(at(1_709_208_000)));
+    assert_eq!(next("0 12 29 2 *", 1_709_208_000), Some(at(1_835_438_400)));
+    assert_eq!(next("0 0 30 2 *", FEB_28_2024), None);
+}
+
+#[test]
+fn cron_days_of_week() {
+    // Friday the 5th, as the day of the month or the day of the week matches
+    assert_eq!(next("0 0 13 * FRI", JAN_1_2024), Some(at(1_704_412_800)));
+    // Sunday the 7th
+    assert_eq!(next("0 0 * * 0", JAN_1_2024), Some(at(1_704_585_600)));
+    assert_eq!(next("0 0 * * 7", JAN_1_2024), Some(at(1_704_585_600)));
+    assert_eq!(next("0 0 * jan sun", JAN_1_2024), Some(at(1_704_585_600)));
+    // Saturday the 13th
+    assert_eq!(next("0 0 13 * ?", JAN_1_2024), Some(at(1_705_104_000)));
+}
+
+#[test]
+fn cron_star_step_days() {
+    // a day field starting with `*` restricts the days together with the other one
+    // Monday the 15th
+    assert_eq!(
+        next("0 0 */2 * MON", JAN_1_2024),
+        Some(at(JAN_1_2024 + 14 * 86_400))
+    );
+    // Thursday, February 1st
+    assert_eq!(
+        next("0 0 1 * */2", JAN_1_2024),
+        Some(at(JAN_1_2024 + 31 * 86_400))
+    );
+}
+
+#[test]
+fn cron_utc_offset() {
+    let schedule: CronSchedule = "0 0 * * *".parse().unwrap();
+    assert_eq!(schedule.utc_offset(), 0);
+
+    let schedule = schedule.with_utc_offset(3600);
+    assert_eq!(schedule.next_after(UNIX_EPOCH), Some(at(23 * 3600)));
+
+    let schedule = schedule.with_utc_offset(-3600);
+    assert_eq!(schedule.next_after(UNIX_EPOCH), Some(at(3600)));
+}
+
+#[test]
+fn cron_invalid() {
+    for expr in &[
+        "* * * *",
+        "* * * * * * *",
+        "60 * * * *",
+        "* 24 * * *",
+        "* * 0 * *",
+        "* * * 13 *",
+        "* * * * 8",
+        "*/0 * * * *",
+        "5-1 * * * *",
+        "a * * * *",
+        "* * * FOO *",
+    ] {
+        assert!(expr.parse::<CronSchedule>().is_err(), "{}", expr);
+    }
+}
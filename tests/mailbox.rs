@@ -0,0 +1,312 @@
+#[macro_use]
+extern crate riker_testkit;
+
+use tezedge_actor_system::actors::*;
+
+use riker_testkit::probe::channel::{probe, ChannelProbe};
+use riker_testkit::probe::{Probe, ProbeReceive};
+
+use std::time::{Duration, Instant};
+
+/// Records received numbers, blocking for a while on `0`
+struct Slow {
+    probe: ChannelProbe<(), u32>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), u32>> for Slow {
+    fn create_args(probe: ChannelProbe<(), u32>) -> Self {
+        Slow { probe }
+    }
+}
+
+impl Actor for Slow {
+    type Msg = u32;
+
+    fn recv(&mut self, _: &Context<Self::Msg>, msg: Self::Msg, _: Sender) {
+        self.probe.event(msg);
+        if msg == 0 {
+            std::thread::sleep(Duration::from_millis(300));
+        }
+    }
+}
+
+/// Records dead letters as "msg reason"
+struct DeadLetterProbe {
+    probe: ChannelProbe<(), String>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for DeadLetterProbe {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        DeadLetterProbe { probe }
+    }
+}
+
+impl Actor for DeadLetterProbe {
+    type Msg = DeadLetter;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        ctx.system.dead_letters().tell(
+            Subscribe {
+                topic: All.into(),
+                actor: Box::new(ctx.myself()),
+            },
+            None,
+        );
+        self.probe.event("subscribed".to_string());
+    }
+
+    fn recv(&mut self, _: &Context<Self::Msg>, msg: Self::Msg, _: Sender) {
+        self.probe.event(format!("{} {:?}", msg.msg, msg.reason));
+    }
+}
+
+fn start(
+    sys: &ActorSystem,
+    capacity: MailboxCapacity,
+) -> (
+    ActorRef<u32>,
+    impl ProbeReceive<Msg = u32>,
+    impl ProbeReceive<Msg = String>,
+) {
+    let (dl_probe, dl_listen) = probe();
+    sys.actor_of_args::<DeadLetterProbe, _>("dead_letters", dl_probe)
+        .unwrap();
+    p_assert_eq!(dl_listen, "subscribed".to_string());
+
+    let (probe, listen) = probe();
+    let props = Props::with_mailbox(Props::new_args::<Slow, _>(probe), capacity);
+    let actor = sys.actor_of_props("slow", props).unwrap();
+
+    // block the actor so that the following messages stay in its mailbox
+    actor.tell(0u32, None);
+    p_assert_eq!(listen, 0);
+
+    (actor, listen, dl_listen)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mailbox_drop_newest() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let capacity = MailboxCapacity::bounded(2, OverflowPolicy::DropNewest);
+    let (actor, listen, dl_listen) = start(&sys, capacity);
+
+    for i in 1..=4u32 {
+        actor.try_tell(i, None).unwrap();
+    }
+
+    p_assert_eq!(dl_listen, "3 MailboxFull".to_string());
+    p_assert_eq!(dl_listen, "4 MailboxFull".to_string());
+
+    p_assert_eq!(listen, 1);
+    p_assert_eq!(listen, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mailbox_drop_oldest() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let capacity = MailboxCapacity::bounded(2, OverflowPolicy::DropOldest);
+    let (actor, listen, dl_listen) = start(&sys, capacity);
+
+    for i in 1..=4u32 {
+        actor.try_tell(i, None).unwrap();
+    }
+
+    p_assert_eq!(dl_listen, "1 MailboxFull".to_string());
+    p_assert_eq!(dl_listen, "2 MailboxFull".to_string());
+
+    p_assert_eq!(listen, 3);
+    p_assert_eq!(listen, 4);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mailbox_reject() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let capacity = MailboxCapacity::bounded(2, OverflowPolicy::Reject);
+    let (actor, listen, dl_listen) = start(&sys, capacity);
+
+    actor.try_tell(1u32, None).unwrap();
+    actor.try_tell(2u32, None).unwrap();
+    let result = actor.try_tell(3u32, None);
+    assert!(matches!(result, Err(SendError::Full(3))));
+
+    p_assert_eq!(dl_listen, "3 MailboxFull".to_string());
+
+    p_assert_eq!(listen, 1);
+    p_assert_eq!(listen, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mailbox_send_waits_for_space() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let capacity = MailboxCapacity::bounded(1, OverflowPolicy::Reject);
+    let (actor, listen, _dl_listen) = start(&sys, capacity);
+
+    let started = Instant::now();
+    actor.send(1u32, None).await.unwrap();
+    actor.send(2u32, None).await.unwrap();
+
+    // the second message only fits once the actor is unblocked
+    assert!(started.elapsed() >= Duration::from_millis(100));
+
+    p_assert_eq!(listen, 1);
+    p_assert_eq!(listen, 2);
+
+    sys.stop(&actor);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let result = actor.send(3u32, None).await;
+    assert!(matches!(result, Err(SendError::Terminated(3))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mailbox_priority() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    // higher tens first, in the order sent within the same tens
+    let kind = MailboxKind::priority(|a: &u32, b: &u32| (a / 10).cmp(&(b / 10)));
+    let (probe, listen) = probe();
+    let props = Props::with_mailbox_kind(Props::new_args::<Slow, _>(probe), kind);
+    let actor = sys.actor_of_props("slow", props).unwrap();
+
+    actor.tell(0u32, None);
+    p_assert_eq!(listen, 0);
+
+    for i in [1u32, 21, 11, 22, 12] {
+        actor.tell(i, None);
+    }
+
+    for i in [21u32, 22, 11, 12, 1] {
+        p_assert_eq!(listen, i);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mailbox_priority_many() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let kind = MailboxKind::priority(|a: &u32, b: &u32| (a / 10).cmp(&(b / 10)));
+    let (probe, listen) = probe();
+    let props = Props::with_mailbox_kind(Props::new_args::<Slow, _>(probe), kind);
+    let actor = sys.actor_of_props("slow", props).unwrap();
+
+    actor.tell(0u32, None);
+    p_assert_eq!(listen, 0);
+
+    // 1 to 999, out of order
+    let sent: Vec<u32> = (1..1000).map(|i| i * 7919 % 1000).collect();
+    for &i in &sent {
+        actor.tell(i, None);
+    }
+
+    let mut expected = sent;
+    expected.sort_by_key(|i| std::cmp::Reverse(i / 10));
+    for i in expected {
+        p_assert_eq!(listen, i);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mailbox_priority_evicts_lowest() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (dl_probe, dl_listen) = probe();
+    sys.actor_of_args::<DeadLetterProbe, _>("dead_letters", dl_probe)
+        .unwrap();
+    p_assert_eq!(dl_listen, "subscribed".to_string());
+
+    let kind = MailboxKind::priority(|a: &u32, b: &u32| (a / 10).cmp(&(b / 10)));
+    let capacity = MailboxCapacity::bounded(3, OverflowPolicy::DropOldest);
+    let (probe, listen) = probe();
+    let props = Props::with_mailbox_kind(Props::new_args::<Slow, _>(probe), kind);
+    let actor = sys
+        .actor_of_props("slow", Props::with_mailbox(props, capacity))
+        .unwrap();
+
+    actor.tell(0u32, None);
+    p_assert_eq!(listen, 0);
+
+    // the newest of the lowest priority messages is discarded
+    for i in [5u32, 25, 15, 35, 1, 6] {
+        actor.try_tell(i, None).unwrap();
+    }
+
+    p_assert_eq!(dl_listen, "5 MailboxFull".to_string());
+    p_assert_eq!(dl_listen, "1 MailboxFull".to_string());
+    p_assert_eq!(dl_listen, "6 MailboxFull".to_string());
+
+    for i in [35u32, 25, 15] {
+        p_assert_eq!(listen, i);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum WorkerMsg {
+    Job(u32),
+    Cancel,
+}
+
+impl ControlMessage for WorkerMsg {
+    fn is_control(&self) -> bool {
+        matches!(self, WorkerMsg::Cancel)
+    }
+}
+
+/// Records jobs, blocking for a while on job `0`, and `Cancel` as `u32::MAX`
+struct Worker {
+    probe: ChannelProbe<(), u32>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), u32>> for Worker {
+    fn create_args(probe: ChannelProbe<(), u32>) -> Self {
+        Worker { probe }
+    }
+}
+
+impl Actor for Worker {
+    type Msg = WorkerMsg;
+
+    fn recv(&mut self, _: &Context<Self::Msg>, msg: Self::Msg, _: Sender) {
+        match msg {
+            WorkerMsg::Job(0) => {
+                self.probe.event(0);
+                std::thread::sleep(Duration::from_millis(300));
+            }
+            WorkerMsg::Job(job) => self.probe.event(job),
+            WorkerMsg::Cancel => self.probe.event(u32::MAX),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mailbox_control_aware() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let props = Props::with_mailbox_kind(
+        Props::new_args::<Worker, _>(probe),
+        MailboxKind::control_aware(),
+    );
+    let actor = sys.actor_of_props("worker", props).unwrap();
+
+    actor.tell(WorkerMsg::Job(0), None);
+    p_assert_eq!(listen, 0);
+
+    actor.tell(WorkerMsg::Job(1), None);
+    actor.tell(WorkerMsg::Job(2), None);
+    actor.tell(WorkerMsg::Cancel, None);
+
+    p_assert_eq!(listen, u32::MAX);
+    p_assert_eq!(listen, 1);
+    p_assert_eq!(listen, 2);
+}
@@ -0,0 +1,75 @@
+use tezedge_actor_system::actors::*;
+
+fn path(path: &str) -> ActorPath {
+    path.parse().unwrap()
+}
+
+#[test]
+fn path_parse() {
+    assert_eq!(path("/user/actor-a/actor_b"), *"/user/actor-a/actor_b");
+    assert!(path("/").is_root());
+
+    assert!("user/actor".parse::<ActorPath>().is_err());
+    assert!("/user//actor".parse::<ActorPath>().is_err());
+    assert!("/user/actor/".parse::<ActorPath>().is_err());
+    assert!("/user/act.or".parse::<ActorPath>().is_err());
+    assert!("".parse::<ActorPath>().is_err());
+    assert!("/user/$".parse::<ActorPath>().is_err());
+}
+
+#[test]
+fn path_navigation() {
+    let actor = path("/user/shell/echo");
+
+    assert_eq!(actor.name(), "echo");
+    assert_eq!(actor.depth(), 3);
+    assert_eq!(
+        actor.segments().collect::<Vec<_>>(),
+        ["user", "shell", "echo"]
+    );
+    assert_eq!(actor.parent(), Some(path("/user/shell")));
+    assert_eq!(path("/user").parent(), Some(ActorPath::root()));
+    assert_eq!(ActorPath::root().parent(), None);
+    assert_eq!(ActorPath::root().name(), "");
+    assert_eq!(ActorPath::root().depth(), 0);
+
+    assert_eq!(
+        actor.child("child").unwrap(),
+        path("/user/shell/echo/child")
+    );
+    assert_eq!(ActorPath::root().child("user").unwrap(), path("/user"));
+    assert!(actor.child("a/b").is_err());
+}
+
+#[test]
+fn path_descendants() {
+    let shell = path("/user/shell");
+
+    assert!(path("/user/shell/echo").is_descendant_of(&shell));
+    assert!(path("/user/shell/echo/child").is_descendant_of(&shell));
+    assert!(shell.is_descendant_of(&ActorPath::root()));
+    assert!(!shell.is_descendant_of(&shell));
+    assert!(!path("/user/shells").is_descendant_of(&shell));
+    assert!(!path("/user").is_descendant_of(&shell));
+}
+
+#[test]
+fn path_resolve() {
+    let echo = path("/user/shell/echo");
+
+    assert_eq!(
+        echo.resolve("../sibling").unwrap(),
+        path("/user/shell/sibling")
+    );
+    assert_eq!(
+        echo.resolve("./child").unwrap(),
+        path("/user/shell/echo/child")
+    );
+    assert_eq!(echo.resolve("../..").unwrap(), path("/user"));
+    assert_eq!(
+        echo.resolve("/system/other").unwrap(),
+        path("/system/other")
+    );
+    assert_eq!(echo.resolve("../../../../..").unwrap(), ActorPath::root());
+    assert!(echo.resolve("../*").is_err());
+}
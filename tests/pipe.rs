@@ -0,0 +1,130 @@
+#[macro_use]
+extern crate riker_testkit;
+
+use tezedge_actor_system::actors::*;
+
+use riker_testkit::probe::channel::{probe, ChannelProbe};
+use riker_testkit::probe::{Probe, ProbeReceive};
+
+use std::time::Duration;
+
+/// Records received numbers with the path of their sender
+struct Recorder {
+    probe: ChannelProbe<(), String>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for Recorder {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        Recorder { probe }
+    }
+}
+
+impl Actor for Recorder {
+    type Msg = u32;
+
+    fn recv(&mut self, _: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
+        let sender = sender.map_or("none".to_string(), |s| s.path().to_string());
+        self.probe.event(format!("{} {}", msg, sender));
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum PiperMsg {
+    /// Doubles the number in a future piped back to itself
+    Double(u32),
+    Doubled(u32),
+    /// Pipes the number to the recorder after the given delay in milliseconds
+    Forward(u32, u64),
+}
+
+struct Piper {
+    probe: ChannelProbe<(), u32>,
+    recorder: ActorRef<u32>,
+}
+
+impl ActorFactoryArgs<(ChannelProbe<(), u32>, ActorRef<u32>)> for Piper {
+    fn create_args((probe, recorder): (ChannelProbe<(), u32>, ActorRef<u32>)) -> Self {
+        Piper { probe, recorder }
+    }
+}
+
+impl Actor for Piper {
+    type Msg = PiperMsg;
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, _: Sender) {
+        match msg {
+            PiperMsg::Double(n) => {
+                let doubled = async move {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    n * 2
+                };
+                ctx.pipe_to_self(doubled, PiperMsg::Doubled);
+            }
+            PiperMsg::Doubled(n) => self.probe.event(n),
+            PiperMsg::Forward(n, delay) => {
+                let delayed = async move {
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    n
+                };
+                ctx.pipe_to(delayed, &self.recorder);
+            }
+        }
+    }
+}
+
+fn start(
+    sys: &ActorSystem,
+) -> (
+    ActorRef<PiperMsg>,
+    ActorRef<u32>,
+    impl ProbeReceive<Msg = u32>,
+    impl ProbeReceive<Msg = String>,
+) {
+    let (rec_probe, rec_listen) = probe();
+    let recorder = sys
+        .actor_of_args::<Recorder, _>("recorder", rec_probe)
+        .unwrap();
+
+    let (probe, listen) = probe();
+    let piper = sys
+        .actor_of_args::<Piper, _>("piper", (probe, recorder.clone()))
+        .unwrap();
+
+    (piper, recorder, listen, rec_listen)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pipe_to_self() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let (piper, _, listen, _) = start(&sys);
+
+    piper.tell(PiperMsg::Double(21), None);
+    p_assert_eq!(listen, 42);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pipe_to_actor() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let (piper, _, _, rec_listen) = start(&sys);
+
+    piper.tell(PiperMsg::Forward(7, 0), None);
+    p_assert_eq!(rec_listen, "7 /user/piper".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pipe_cancelled_on_stop() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let (piper, recorder, _, rec_listen) = start(&sys);
+
+    piper.tell(PiperMsg::Forward(1, 200), None);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    sys.stop(&piper);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // the piped number was never delivered
+    recorder.tell(2u32, None);
+    p_assert_eq!(rec_listen, "2 none".to_string());
+}
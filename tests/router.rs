@@ -0,0 +1,345 @@
+#[macro_use]
+extern crate riker_testkit;
+
+use tezedge_actor_system::actors::*;
+
+use riker_testkit::probe::channel::{probe, ChannelProbe};
+use riker_testkit::probe::{Probe, ProbeReceive};
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+/// Records received numbers as "name number", blocking for a while on `0`
+struct Worker {
+    probe: ChannelProbe<(), String>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for Worker {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        Worker { probe }
+    }
+}
+
+impl Actor for Worker {
+    type Msg = u32;
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, _: Sender) {
+        self.probe.event(format!("{} {}", ctx.myself.name(), msg));
+        if msg == 0 {
+            // lets the other actors run on this thread's runtime worker meanwhile
+            tokio::task::block_in_place(|| std::thread::sleep(Duration::from_millis(300)));
+        }
+    }
+}
+
+fn pool(
+    sys: &ActorSystem,
+    size: usize,
+    logic: RoutingLogic<u32>,
+) -> (RouterRef<u32>, impl ProbeReceive<Msg = String>) {
+    let (probe, listen) = probe();
+    let props = RouterConfig::pool(size, Props::new_args::<Worker, _>(probe), logic).props();
+    (sys.actor_of_props("pool", props).unwrap(), listen)
+}
+
+/// Receives `n` events and returns the routee names, sorted
+fn routees(listen: &impl ProbeReceive<Msg = String>, n: usize) -> Vec<String> {
+    let mut names: Vec<String> = (0..n)
+        .map(|_| listen.recv().split(' ').next().unwrap().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn router_round_robin() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let (router, listen) = pool(&sys, 3, RoutingLogic::RoundRobin);
+
+    for i in 1..=6u32 {
+        router.tell(i, None);
+    }
+
+    let expected = [
+        "routee-0", "routee-0", "routee-1", "routee-1", "routee-2", "routee-2",
+    ];
+    assert_eq!(routees(&listen, 6), expected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn router_random() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let (router, listen) = pool(&sys, 3, RoutingLogic::Random);
+
+    for i in 1..=30u32 {
+        router.tell(i, None);
+    }
+
+    let mut names = routees(&listen, 30);
+    names.dedup();
+    assert!(names.len() > 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn router_smallest_mailbox() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let (router, listen) = pool(&sys, 2, RoutingLogic::SmallestMailbox);
+
+    // keeps the first routee busy
+    router.tell(0u32, None);
+    p_assert_eq!(listen, "routee-0 0".to_string());
+
+    router.tell(1u32, None);
+    p_assert_eq!(listen, "routee-1 1".to_string());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    router.tell(2u32, None);
+    p_assert_eq!(listen, "routee-1 2".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn router_broadcast() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let (router, listen) = pool(&sys, 3, RoutingLogic::RoundRobin);
+
+    router.tell(Broadcast(7u32), None);
+
+    let expected = ["routee-0", "routee-1", "routee-2"];
+    assert_eq!(routees(&listen, 3), expected);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn router_group() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let a = sys.actor_of_args::<Worker, _>("a", probe.clone()).unwrap();
+    sys.actor_of_args::<Worker, _>("b", probe).unwrap();
+
+    let props = RouterConfig::group(&["/user/a", "/user/b"], RoutingLogic::RoundRobin).props();
+    let router = sys.actor_of_props("group", props).unwrap();
+
+    for i in 1..=4u32 {
+        router.tell(i, None);
+    }
+    assert_eq!(routees(&listen, 4), ["a", "a", "b", "b"]);
+
+    // terminated routees are removed
+    sys.stop(&a);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    for i in 1..=2u32 {
+        router.tell(i, None);
+    }
+    assert_eq!(routees(&listen, 2), ["b", "b"]);
+}
+
+#[derive(Clone, Debug)]
+pub struct Keyed(u32);
+
+impl ConsistentHashable for Keyed {
+    type Key = u32;
+
+    fn hash_key(&self) -> u32 {
+        self.0
+    }
+}
+
+/// Records received keys as "name key"
+struct KeyedWorker {
+    probe: ChannelProbe<(), String>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for KeyedWorker {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        KeyedWorker { probe }
+    }
+}
+
+impl Actor for KeyedWorker {
+    type Msg = Keyed;
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, _: Sender) {
+        self.probe.event(format!("{} {}", ctx.myself.name(), msg.0));
+    }
+}
+
+/// Sends keys `0..n` and returns the routee that received each key
+fn route_keys(
+    router: &RouterRef<Keyed>,
+    listen: &impl ProbeReceive<Msg = String>,
+    n: u32,
+) -> HashMap<u32, String> {
+    for key in 0..n {
+        router.tell(Keyed(key), None);
+    }
+
+    (0..n)
+        .map(|_| {
+            let event = listen.recv();
+            let (name, key) = event.split_once(' ').unwrap();
+            (key.parse().unwrap(), name.to_string())
+        })
+        .collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn router_consistent_hashing() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let props = RouterConfig::pool(
+        3,
+        Props::new_args::<KeyedWorker, _>(probe),
+        RoutingLogic::consistent_hashing(50),
+    )
+    .props();
+    let router = sys.actor_of_props("pool", props).unwrap();
+
+    // the same key is always routed to the same routee
+    let first = route_keys(&router, &listen, 30);
+    let second = route_keys(&router, &listen, 30);
+    assert_eq!(first, second);
+
+    let used: HashSet<&String> = first.values().collect();
+    assert!(used.len() > 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn router_consistent_hashing_remove_routee() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    for name in ["a", "b"] {
+        sys.actor_of_args::<KeyedWorker, _>(name, probe.clone())
+            .unwrap();
+    }
+    let c = sys.actor_of_args::<KeyedWorker, _>("c", probe).unwrap();
+
+    let paths = ["/user/a", "/user/b", "/user/c"];
+    let props = RouterConfig::group(&paths, RoutingLogic::consistent_hashing(50)).props();
+    let router = sys.actor_of_props("group", props).unwrap();
+
+    let before = route_keys(&router, &listen, 30);
+
+    sys.stop(&c);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let after = route_keys(&router, &listen, 30);
+
+    // only the keys of the removed routee are remapped
+    for (key, name) in before {
+        if name == "c" {
+            assert_ne!(after[&key], "c");
+        } else {
+            assert_eq!(after[&key], name);
+        }
+    }
+}
+
+/// Waits until `router` has `n` routees, failing after a few seconds
+async fn wait_for_routees(router: &RouterRef<u32>, n: usize) {
+    for _ in 0..100 {
+        if router.children().count() == n {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("router has {} routees", router.children().count());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn router_resizer() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let resizer = Resizer::new(1, 3).with_interval(Duration::from_millis(50));
+    let props = RouterConfig::pool(
+        1,
+        Props::new_args::<Worker, _>(probe),
+        RoutingLogic::RoundRobin,
+    )
+    .with_resizer(resizer)
+    .props();
+    let router = sys.actor_of_props("pool", props).unwrap();
+
+    // the only routee is busy with more messages queued
+    for _ in 0..3 {
+        router.tell(0u32, None);
+    }
+    wait_for_routees(&router, 2).await;
+
+    for i in 1..=4u32 {
+        router.tell(i, None);
+    }
+
+    // all messages are processed, then the pool shrinks back
+    let names = routees(&listen, 7);
+    assert!(names.contains(&"routee-1".to_string()));
+    wait_for_routees(&router, 1).await;
+}
+
+/// Records dead letters as "msg reason"
+struct DeadLetterProbe {
+    probe: ChannelProbe<(), String>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for DeadLetterProbe {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        DeadLetterProbe { probe }
+    }
+}
+
+impl Actor for DeadLetterProbe {
+    type Msg = DeadLetter;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        ctx.system.dead_letters().tell(
+            Subscribe {
+                topic: All.into(),
+                actor: Box::new(ctx.myself()),
+            },
+            None,
+        );
+        self.probe.event("subscribed".to_string());
+    }
+
+    fn recv(&mut self, _: &Context<Self::Msg>, msg: Self::Msg, _: Sender) {
+        self.probe.event(format!("{} {:?}", msg.msg, msg.reason));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn router_undelivered_to_dead_letters() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (dl_probe, dl_listen) = probe();
+    sys.actor_of_args::<DeadLetterProbe, _>("dead_letters", dl_probe)
+        .unwrap();
+    p_assert_eq!(dl_listen, "subscribed".to_string());
+
+    let (probe, listen) = probe();
+    let capacity = MailboxCapacity::bounded(1, OverflowPolicy::Reject);
+    let props = Props::with_mailbox(Props::new_args::<Worker, _>(probe.clone()), capacity);
+    sys.actor_of_props("a", props).unwrap();
+    // a routee of another message type, which never receives the routed messages
+    sys.actor_of_args::<KeyedWorker, _>("keyed", probe).unwrap();
+
+    let props = RouterConfig::group(&["/user/a", "/user/keyed"], RoutingLogic::Broadcast).props();
+    let router = sys.actor_of_props("group", props).unwrap();
+
+    // the routee is busy with `0`, and its mailbox is full after `1`
+    router.tell(0u32, None);
+    p_assert_eq!(listen, "a 0".to_string());
+    router.tell(1u32, None);
+    router.tell(2u32, None);
+
+    p_assert_eq!(dl_listen, "2 MailboxFull".to_string());
+    p_assert_eq!(listen, "a 1".to_string());
+}
@@ -2,11 +2,16 @@
 extern crate riker_testkit;
 
 use tezedge_actor_system::actors::*;
+use tezedge_actor_system::load_config;
 
 use riker_testkit::probe::channel::{probe, ChannelProbe};
 use riker_testkit::probe::{Probe, ProbeReceive};
 
-use std::time::Duration;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
 #[derive(Clone, Debug)]
 pub struct TestProbe(ChannelProbe<(), ()>);
@@ -34,7 +39,8 @@ impl Receive<TestProbe> for ScheduleOnce {
     fn receive(&mut self, ctx: &Context<ScheduleOnceMsg>, msg: TestProbe, _sender: Sender) {
         self.probe = Some(msg);
         // reschedule an Empty to be sent to myself()
-        ctx.schedule_once(Duration::from_millis(200), ctx.myself(), None, SomeMessage);
+        ctx.schedule_once(Duration::from_millis(200), ctx.myself(), None, SomeMessage)
+            .detach();
     }
 }
 
@@ -56,7 +62,8 @@ async fn schedule_once() {
     let (probe, listen) = probe();
 
     // use scheduler to set up probe
-    sys.schedule_once(Duration::from_millis(200), actor, None, TestProbe(probe));
+    sys.schedule_once(Duration::from_millis(200), actor, None, TestProbe(probe))
+        .detach();
     p_assert_eq!(listen, ());
 }
 
@@ -66,7 +73,7 @@ async fn schedule_once() {
 struct ScheduleRepeat {
     probe: Option<TestProbe>,
     counter: u32,
-    schedule_id: Option<ScheduleId>,
+    schedule: Option<ScheduleHandle>,
 }
 
 impl Actor for ScheduleRepeat {
@@ -83,24 +90,24 @@ impl Receive<TestProbe> for ScheduleRepeat {
     fn receive(&mut self, ctx: &Context<Self::Msg>, msg: TestProbe, _sender: Sender) {
         self.probe = Some(msg);
         // schedule Message to be repeatedly sent to myself
-        // and store the job id to cancel it later
-        let id = ctx.schedule(
+        // and keep its handle to cancel it later
+        let schedule = ctx.schedule(
             Duration::from_millis(200),
             Duration::from_millis(200),
             ctx.myself(),
             None,
             SomeMessage,
         );
-        self.schedule_id = Some(id);
+        self.schedule = Some(schedule);
     }
 }
 
 impl Receive<SomeMessage> for ScheduleRepeat {
     type Msg = ScheduleRepeatMsg;
 
-    fn receive(&mut self, ctx: &Context<Self::Msg>, _msg: SomeMessage, _sender: Sender) {
+    fn receive(&mut self, _ctx: &Context<Self::Msg>, _msg: SomeMessage, _sender: Sender) {
         if self.counter == 5 {
-            ctx.cancel_schedule(self.schedule_id.unwrap());
+            self.schedule.take().unwrap().cancel();
             self.probe.as_ref().unwrap().0.event(());
         } else {
             self.counter += 1;
@@ -121,3 +128,367 @@ async fn schedule_repeat() {
 
     p_assert_eq!(listen, ());
 }
+
+// *** Timer precision and capacity tests ***
+#[tokio::test(flavor = "multi_thread")]
+async fn schedule_precision() {
+    let clock = VirtualClock::new();
+    let sys = SystemBuilder::new()
+        .exec(tokio::runtime::Handle::current().into())
+        .timer(clock.timer())
+        .create()
+        .unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Record, _>("record", probe).unwrap();
+
+    let mut delays = [7, 3, 5, 1, 9];
+    for delay in delays {
+        let msg = delay.to_string();
+        sys.schedule_once(Duration::from_secs(delay), actor.clone(), None, msg)
+            .detach();
+    }
+
+    // each message is sent by its deadline and not before, in the order of deadlines
+    delays.sort();
+    for (sent, &delay) in delays.iter().enumerate() {
+        let deadline = Duration::from_secs(delay);
+        clock.advance(deadline - clock.elapsed() - Duration::from_millis(500));
+        assert_eq!(clock.scheduled(), delays.len() - sent);
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(clock.scheduled(), delays.len() - sent - 1);
+        p_assert_eq!(listen, delay.to_string());
+    }
+}
+
+struct Stamp {
+    probe: ChannelProbe<(), Instant>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), Instant>> for Stamp {
+    fn create_args(probe: ChannelProbe<(), Instant>) -> Self {
+        Stamp { probe }
+    }
+}
+
+impl Actor for Stamp {
+    type Msg = ();
+
+    fn recv(&mut self, _ctx: &Context<Self::Msg>, _msg: Self::Msg, _sender: Sender) {
+        self.probe.event(Instant::now());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn schedule_precision_thread() {
+    let backend = tokio::runtime::Handle::current().into();
+    let mut cfg = load_config();
+    cfg.scheduler.backend = TimerBackend::Thread;
+    let sys = ActorSystem::with_config("precision", backend, cfg).unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Stamp, _>("stamp", probe).unwrap();
+
+    // the timer thread wakes up at the deadline, not on a polling interval
+    for _ in 0..5 {
+        let start = Instant::now();
+        sys.schedule_once(Duration::from_millis(3), actor.clone(), None, ())
+            .detach();
+        let elapsed = listen.recv().duration_since(start);
+        assert!(elapsed >= Duration::from_millis(3), "{:?}", elapsed);
+        assert!(elapsed < Duration::from_millis(500), "{:?}", elapsed);
+    }
+}
+
+struct Count {
+    count: Arc<AtomicUsize>,
+}
+
+impl ActorFactoryArgs<Arc<AtomicUsize>> for Count {
+    fn create_args(count: Arc<AtomicUsize>) -> Self {
+        Count { count }
+    }
+}
+
+impl Actor for Count {
+    type Msg = u32;
+
+    fn recv(&mut self, _ctx: &Context<Self::Msg>, _msg: Self::Msg, _sender: Sender) {
+        self.count.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn schedule_many_and_cancel() {
+    let clock = VirtualClock::new();
+    let sys = SystemBuilder::new()
+        .exec(tokio::runtime::Handle::current().into())
+        .timer(clock.timer())
+        .create()
+        .unwrap();
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let actor = sys
+        .actor_of_args::<Count, _>("count", count.clone())
+        .unwrap();
+
+    let ids: Vec<_> = (0..100_000u32)
+        .map(|i| {
+            let delay = Duration::from_millis(1000 + u64::from(i % 100));
+            sys.schedule_once(delay, actor.clone(), None, i).detach()
+        })
+        .collect();
+
+    // cancel every other job
+    for id in ids.iter().step_by(2) {
+        sys.cancel_schedule(*id);
+    }
+    assert_eq!(clock.scheduled(), 50_000);
+
+    clock.advance(Duration::from_millis(1099));
+    assert_eq!(clock.scheduled(), 0);
+
+    // wait for the actor to count the messages sent
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while count.load(Ordering::SeqCst) < 50_000 && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    assert_eq!(count.load(Ordering::SeqCst), 50_000);
+}
+
+#[tokio::test(start_paused = true)]
+async fn schedule_tokio_paused_time() {
+    let backend = tokio::runtime::Handle::current().into();
+    let mut cfg = load_config();
+    cfg.scheduler.backend = TimerBackend::Tokio;
+    let sys = ActorSystem::with_config("paused-time", backend, cfg).unwrap();
+
+    let count = Arc::new(AtomicUsize::new(0));
+    let actor = sys
+        .actor_of_args::<Count, _>("count", count.clone())
+        .unwrap();
+
+    let minute = Duration::from_secs(60);
+    let schedule = sys.schedule(minute, minute, actor, None, 0u32);
+
+    tokio::time::sleep(minute / 2).await;
+    assert_eq!(count.load(Ordering::SeqCst), 0);
+
+    tokio::time::sleep(minute).await;
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    tokio::time::sleep(minute * 10).await;
+    assert_eq!(count.load(Ordering::SeqCst), 11);
+
+    schedule.cancel();
+    tokio::time::sleep(minute * 60).await;
+    assert_eq!(count.load(Ordering::SeqCst), 11);
+}
+
+#[tokio::test(start_paused = true)]
+async fn schedule_fixed_rate_and_fixed_delay() {
+    let backend = tokio::runtime::Handle::current().into();
+    let mut cfg = load_config();
+    cfg.scheduler.backend = TimerBackend::Tokio;
+    let sys = ActorSystem::with_config("repeat-modes", backend, cfg).unwrap();
+
+    let rate_count = Arc::new(AtomicUsize::new(0));
+    let rate = sys
+        .actor_of_args::<Count, _>("rate", rate_count.clone())
+        .unwrap();
+    let delay_count = Arc::new(AtomicUsize::new(0));
+    let delay = sys
+        .actor_of_args::<Count, _>("delay", delay_count.clone())
+        .unwrap();
+
+    let minute = Duration::from_secs(60);
+    let fixed_rate = RepeatSchedule::fixed_rate(minute, minute);
+    let _rate = sys.schedule_with(fixed_rate, rate, None, 0u32);
+    let fixed_delay = RepeatSchedule::fixed_delay(minute, minute);
+    let _delay = sys.schedule_with(fixed_delay, delay, None, 0u32);
+    tokio::time::sleep(minute / 2).await;
+
+    // the timer wakes up 10 minutes late
+    tokio::time::advance(minute * 10).await;
+    tokio::time::sleep(minute / 4).await;
+    assert_eq!(rate_count.load(Ordering::SeqCst), 10);
+    assert_eq!(delay_count.load(Ordering::SeqCst), 1);
+
+    tokio::time::sleep(minute).await;
+    assert_eq!(rate_count.load(Ordering::SeqCst), 11);
+    assert_eq!(delay_count.load(Ordering::SeqCst), 2);
+}
+
+struct Record {
+    probe: ChannelProbe<(), String>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for Record {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        Record { probe }
+    }
+}
+
+impl Actor for Record {
+    type Msg = String;
+
+    fn recv(&mut self, _ctx: &Context<Self::Msg>, msg: Self::Msg, _sender: Sender) {
+        self.probe.event(msg);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn schedule_virtual_clock() {
+    let clock = VirtualClock::new();
+    let sys = SystemBuilder::new()
+        .exec(tokio::runtime::Handle::current().into())
+        .timer(clock.timer())
+        .create()
+        .unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Record, _>("record", probe).unwrap();
+
+    let minute = Duration::from_secs(60);
+    let tick = sys.schedule(minute, minute, actor.clone(), None, "tick".to_string());
+    sys.schedule_once(minute * 9 / 2, actor.clone(), None, "once".to_string())
+        .detach();
+    sys.schedule_once(minute * 30, actor, None, "later".to_string())
+        .detach();
+    assert_eq!(clock.scheduled(), 3);
+
+    clock.advance(minute * 10);
+    assert_eq!(clock.elapsed(), minute * 10);
+
+    let mut expected = vec!["tick"; 4];
+    expected.push("once");
+    expected.extend(vec!["tick"; 6]);
+    for msg in expected {
+        p_assert_eq!(listen, msg.to_string());
+    }
+
+    tick.cancel();
+    clock.advance(minute * 30);
+    p_assert_eq!(listen, "later".to_string());
+    assert_eq!(clock.scheduled(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn schedule_cron() {
+    // 2024-02-28 23:59:00 UTC
+    let clock = VirtualClock::starting_at(UNIX_EPOCH + Duration::from_secs(1_709_164_740));
+    let sys = SystemBuilder::new()
+        .exec(tokio::runtime::Handle::current().into())
+        .timer(clock.timer())
+        .create()
+        .unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Record, _>("record", probe).unwrap();
+
+    let hourly: CronSchedule = "0 * * * *".parse().unwrap();
+    let leap_day: CronSchedule = "30 0 29 2 *".parse().unwrap();
+    let schedule = sys.schedule_cron(hourly, actor.clone(), None, "hourly".to_string());
+    sys.schedule_cron(leap_day, actor, None, "leap day".to_string())
+        .detach();
+
+    // 00:00, 00:30 then 01:00
+    clock.advance(Duration::from_secs(61 * 60));
+    p_assert_eq!(listen, "hourly".to_string());
+    p_assert_eq!(listen, "leap day".to_string());
+    p_assert_eq!(listen, "hourly".to_string());
+
+    schedule.cancel();
+    assert_eq!(clock.scheduled(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn schedule_cron_missed_fire() {
+    let clock = VirtualClock::starting_at(UNIX_EPOCH);
+    let sys = SystemBuilder::new()
+        .exec(tokio::runtime::Handle::current().into())
+        .timer(clock.timer())
+        .create()
+        .unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Record, _>("record", probe).unwrap();
+
+    let every_minute: CronSchedule = "* * * * *".parse().unwrap();
+    for missed in [MissedFire::FireOnce, MissedFire::Skip, MissedFire::FireAll] {
+        let msg = format!("{:?}", missed);
+        let schedule = every_minute.clone().on_missed(missed);
+        sys.schedule_cron(schedule, actor.clone(), None, msg)
+            .detach();
+    }
+
+    // suspended for 3 minutes, waking up 10 seconds before the first fire time
+    clock.jump_to(UNIX_EPOCH + Duration::from_secs(3 * 60 + 50));
+    clock.advance(Duration::from_secs(60));
+
+    let mut received: Vec<String> = (0..5).map(|_| listen.recv()).collect();
+    received.sort();
+    assert_eq!(
+        received,
+        ["FireAll", "FireAll", "FireAll", "FireAll", "FireOnce"]
+    );
+
+    // back on schedule, at 00:05
+    clock.advance(Duration::from_secs(60));
+    let mut received: Vec<String> = (0..3).map(|_| listen.recv()).collect();
+    received.sort();
+    assert_eq!(received, ["FireAll", "FireOnce", "Skip"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn schedule_jitter_and_max_fires() {
+    let clock = VirtualClock::new();
+    let sys = SystemBuilder::new()
+        .exec(tokio::runtime::Handle::current().into())
+        .timer(clock.timer())
+        .create()
+        .unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Record, _>("record", probe).unwrap();
+
+    let minute = Duration::from_secs(60);
+    let jittered = RepeatSchedule::fixed_rate(minute, minute).with_jitter(minute / 2);
+    let jittered = sys.schedule_with(jittered, actor.clone(), None, "jitter".to_string());
+    let limited = RepeatSchedule::fixed_delay(minute, minute).with_max_fires(3);
+    let _limited = sys.schedule_with(limited, actor, None, "limited".to_string());
+    assert_eq!(clock.scheduled(), 2);
+
+    // each jittered message is sent within half a minute of its fire time
+    clock.advance(minute * 10 + minute / 2);
+    let received: Vec<String> = (0..13).map(|_| listen.recv()).collect();
+    assert_eq!(received.iter().filter(|msg| *msg == "jitter").count(), 10);
+    assert_eq!(received.iter().filter(|msg| *msg == "limited").count(), 3);
+    assert_eq!(clock.scheduled(), 1);
+
+    // dropping the handle cancels the schedule
+    drop(jittered);
+    assert_eq!(clock.scheduled(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn schedule_unbounded_jitter() {
+    let clock = VirtualClock::new();
+    let sys = SystemBuilder::new()
+        .exec(tokio::runtime::Handle::current().into())
+        .timer(clock.timer())
+        .create()
+        .unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Record, _>("record", probe).unwrap();
+
+    // the first message is due right away, the next one is delayed by up to centuries
+    let schedule = RepeatSchedule::fixed_rate(Duration::ZERO, Duration::from_secs(60))
+        .with_jitter(Duration::MAX);
+    let _schedule = sys.schedule_with(schedule, actor, None, "jitter".to_string());
+
+    p_assert_eq!(listen, "jitter".to_string());
+    assert_eq!(clock.scheduled(), 1);
+}
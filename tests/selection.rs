@@ -0,0 +1,123 @@
+#[macro_use]
+extern crate riker_testkit;
+
+use tezedge_actor_system::actors::*;
+
+use riker_testkit::probe::channel::{probe, ChannelProbe};
+use riker_testkit::probe::{Probe, ProbeReceive};
+
+/// Starts two `Echo` children, "echo-1" and "echo-2"
+struct Shell {
+    probe: ChannelProbe<(), String>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for Shell {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        Shell { probe }
+    }
+}
+
+impl Actor for Shell {
+    type Msg = u32;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        ctx.actor_of_args::<Echo, _>("echo-1", self.probe.clone())
+            .unwrap();
+        ctx.actor_of_args::<Echo, _>("echo-2", self.probe.clone())
+            .unwrap();
+    }
+
+    fn recv(&mut self, _: &Context<Self::Msg>, _: Self::Msg, _: Sender) {}
+}
+
+/// Records received messages as "name msg", forwarding "sibling" to `../echo-2`
+struct Echo {
+    probe: ChannelProbe<(), String>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for Echo {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        Echo { probe }
+    }
+}
+
+impl Actor for Echo {
+    type Msg = String;
+
+    fn post_start(&mut self, ctx: &Context<Self::Msg>) {
+        self.probe.event(format!("{} started", ctx.myself.name()));
+    }
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, _: Sender) {
+        if msg == "sibling" {
+            let selection = ctx.select("../echo-2").unwrap();
+            selection.try_tell(format!("from {}", ctx.myself.name()), None);
+        } else {
+            self.probe.event(format!("{} {}", ctx.myself.name(), msg));
+        }
+    }
+}
+
+fn shell(sys: &ActorSystem) -> impl ProbeReceive<Msg = String> {
+    let (probe, listen) = probe();
+    sys.actor_of_args::<Shell, _>("shell", probe).unwrap();
+
+    let mut started = vec![listen.recv(), listen.recv()];
+    started.sort();
+    assert_eq!(started, ["echo-1 started", "echo-2 started"]);
+    listen
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn select_wildcard() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let listen = shell(&sys);
+
+    let selection = sys.select("/user/shell/*").unwrap();
+    assert_eq!(selection.try_tell("hello".to_string(), None), 2);
+
+    let mut received = vec![listen.recv(), listen.recv()];
+    received.sort();
+    assert_eq!(received, ["echo-1 hello", "echo-2 hello"]);
+
+    // actors with a different message type are skipped
+    assert_eq!(sys.select("/user/*").unwrap().try_tell(1u64, None), 0);
+    assert_eq!(sys.select("shell/echo-?").unwrap().resolve().len(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn select_relative() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let listen = shell(&sys);
+
+    let selection = sys.select("/user/shell/echo-1").unwrap();
+    assert_eq!(selection.try_tell("sibling".to_string(), None), 1);
+    p_assert_eq!(listen, "echo-2 from echo-1".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn select_invalid_path() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    assert!(sys.select("/user/shell.echo").is_err());
+    assert!(sys.select("/user/shell echo").is_err());
+    assert!(sys.select("/user/missing").unwrap().resolve().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn lookup_typed() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let listen = shell(&sys);
+
+    let echo = sys.lookup::<String>("/user/shell/echo-1").unwrap();
+    echo.tell("typed".to_string(), None);
+    p_assert_eq!(listen, "echo-1 typed".to_string());
+
+    assert!(sys.lookup::<u32>("/user/shell").is_some());
+    assert!(sys.lookup::<u32>("/user/shell/echo-1").is_none());
+    assert!(sys.lookup::<String>("/user/shell/missing").is_none());
+}
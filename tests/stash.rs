@@ -0,0 +1,196 @@
+#[macro_use]
+extern crate riker_testkit;
+
+use tezedge_actor_system::actors::*;
+
+use riker_testkit::probe::channel::{probe, ChannelProbe};
+use riker_testkit::probe::{Probe, ProbeReceive};
+
+#[derive(Clone, Debug)]
+pub enum StashMsg {
+    Work(u32),
+    Ready,
+    Fail,
+    Flood,
+}
+
+/// Stashes work until it is ready, recording handled work
+struct Stasher {
+    probe: ChannelProbe<(), String>,
+    keep_stash: bool,
+    ready: bool,
+}
+
+impl ActorFactoryArgs<(ChannelProbe<(), String>, bool)> for Stasher {
+    fn create_args((probe, keep_stash): (ChannelProbe<(), String>, bool)) -> Self {
+        Stasher {
+            probe,
+            keep_stash,
+            ready: false,
+        }
+    }
+}
+
+impl Actor for Stasher {
+    type Msg = StashMsg;
+
+    fn keep_stash_on_restart(&self) -> bool {
+        self.keep_stash
+    }
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
+        match msg {
+            StashMsg::Work(n) if self.ready => self.probe.event(format!("work {}", n)),
+            StashMsg::Work(_) => {
+                ctx.stash(msg, sender).unwrap();
+                self.probe.event("stashed".to_string());
+            }
+            StashMsg::Ready => {
+                self.ready = true;
+                ctx.unstash_all();
+            }
+            StashMsg::Fail => panic!("// TEST PANIC // TEST PANIC // TEST PANIC //"),
+            StashMsg::Flood => {
+                let stashed = (0..)
+                    .take_while(|n| ctx.stash(StashMsg::Work(*n), None).is_ok())
+                    .count();
+                self.probe.event(format!("flooded {}", stashed));
+            }
+        }
+    }
+}
+
+/// Records dead letters as "msg reason"
+struct DeadLetterProbe {
+    probe: ChannelProbe<(), String>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for DeadLetterProbe {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        DeadLetterProbe { probe }
+    }
+}
+
+impl Actor for DeadLetterProbe {
+    type Msg = DeadLetter;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        ctx.system.dead_letters().tell(
+            Subscribe {
+                topic: All.into(),
+                actor: Box::new(ctx.myself()),
+            },
+            None,
+        );
+        self.probe.event("subscribed".to_string());
+    }
+
+    fn recv(&mut self, _: &Context<Self::Msg>, msg: Self::Msg, _: Sender) {
+        self.probe.event(format!("{} {:?}", msg.msg, msg.reason));
+    }
+}
+
+fn dead_letters(sys: &ActorSystem) -> impl ProbeReceive<Msg = String> {
+    let (probe, listen) = probe();
+    sys.actor_of_args::<DeadLetterProbe, _>("dead_letters", probe)
+        .unwrap();
+    p_assert_eq!(listen, "subscribed".to_string());
+    listen
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stash_unstash_all() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys
+        .actor_of_args::<Stasher, _>("stasher", (probe, false))
+        .unwrap();
+
+    actor.tell(StashMsg::Work(1), None);
+    actor.tell(StashMsg::Work(2), None);
+    p_assert_eq!(listen, "stashed".to_string());
+    p_assert_eq!(listen, "stashed".to_string());
+
+    // unstashed messages are received before messages already in the mailbox
+    actor.tell(StashMsg::Ready, None);
+    actor.tell(StashMsg::Work(3), None);
+    p_assert_eq!(listen, "work 1".to_string());
+    p_assert_eq!(listen, "work 2".to_string());
+    p_assert_eq!(listen, "work 3".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stash_full() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys
+        .actor_of_args::<Stasher, _>("stasher", (probe, false))
+        .unwrap();
+
+    actor.tell(StashMsg::Flood, None);
+    p_assert_eq!(listen, "flooded 1000".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stash_dropped_on_stop() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let dl_listen = dead_letters(&sys);
+
+    let (probe, listen) = probe();
+    let actor = sys
+        .actor_of_args::<Stasher, _>("stasher", (probe, false))
+        .unwrap();
+
+    actor.tell(StashMsg::Work(1), None);
+    p_assert_eq!(listen, "stashed".to_string());
+
+    sys.stop(&actor);
+    p_assert_eq!(dl_listen, "Work(1) Terminated".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stash_dropped_on_restart() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+    let dl_listen = dead_letters(&sys);
+
+    let (probe, listen) = probe();
+    let actor = sys
+        .actor_of_args::<Stasher, _>("stasher", (probe, false))
+        .unwrap();
+
+    actor.tell(StashMsg::Work(1), None);
+    p_assert_eq!(listen, "stashed".to_string());
+
+    actor.tell(StashMsg::Fail, None);
+    p_assert_eq!(dl_listen, "Work(1) Restarted".to_string());
+
+    actor.tell(StashMsg::Ready, None);
+    actor.tell(StashMsg::Work(2), None);
+    p_assert_eq!(listen, "work 2".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stash_kept_on_restart() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    let actor = sys
+        .actor_of_args::<Stasher, _>("stasher", (probe, true))
+        .unwrap();
+
+    actor.tell(StashMsg::Work(1), None);
+    p_assert_eq!(listen, "stashed".to_string());
+
+    actor.tell(StashMsg::Fail, None);
+    actor.tell(StashMsg::Ready, None);
+    actor.tell(StashMsg::Work(2), None);
+    p_assert_eq!(listen, "work 1".to_string());
+    p_assert_eq!(listen, "work 2".to_string());
+}
@@ -112,3 +112,281 @@ async fn supervision_restart_failed_actor() {
         p_assert_eq!(listen, ());
     }
 }
+
+// Test Resume, Stop and AllForOne strategies
+#[derive(Clone, Debug)]
+pub struct Count;
+
+#[derive(Clone, Debug)]
+pub struct CountProbe(ChannelProbe<(), u32>);
+
+#[actor(Count, Panic, CountProbe)]
+#[derive(Default)]
+struct Counter {
+    count: u32,
+}
+
+impl Actor for Counter {
+    type Msg = CounterMsg;
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
+        self.receive(ctx, msg, sender);
+    }
+}
+
+impl Receive<Count> for Counter {
+    type Msg = CounterMsg;
+
+    fn receive(&mut self, _ctx: &Context<Self::Msg>, _msg: Count, _sender: Sender) {
+        self.count += 1;
+    }
+}
+
+impl Receive<Panic> for Counter {
+    type Msg = CounterMsg;
+
+    fn receive(&mut self, _ctx: &Context<Self::Msg>, _msg: Panic, _sender: Sender) {
+        panic!("// TEST PANIC // TEST PANIC // TEST PANIC //");
+    }
+}
+
+impl Receive<CountProbe> for Counter {
+    type Msg = CounterMsg;
+
+    fn receive(&mut self, _ctx: &Context<Self::Msg>, msg: CountProbe, _sender: Sender) {
+        msg.0.event(self.count);
+    }
+}
+
+#[actor(Count, Panic, CountProbe)]
+struct StrategySup {
+    strategy: SupervisorStrategy,
+    children: Vec<ActorRef<CounterMsg>>,
+}
+
+impl ActorFactoryArgs<SupervisorStrategy> for StrategySup {
+    fn create_args(strategy: SupervisorStrategy) -> Self {
+        StrategySup {
+            strategy,
+            children: Vec::new(),
+        }
+    }
+}
+
+impl Actor for StrategySup {
+    type Msg = StrategySupMsg;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        self.children = vec![
+            ctx.actor_of::<Counter>("child_a").unwrap(),
+            ctx.actor_of::<Counter>("child_b").unwrap(),
+        ];
+    }
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
+        self.receive(ctx, msg, sender)
+    }
+
+    fn supervisor_strategy(&self) -> SupervisorStrategy {
+        self.strategy.clone()
+    }
+}
+
+impl Receive<Count> for StrategySup {
+    type Msg = StrategySupMsg;
+
+    fn receive(&mut self, _ctx: &Context<Self::Msg>, msg: Count, sender: Sender) {
+        for child in &self.children {
+            child.tell(msg.clone(), sender.clone());
+        }
+    }
+}
+
+impl Receive<Panic> for StrategySup {
+    type Msg = StrategySupMsg;
+
+    fn receive(&mut self, _ctx: &Context<Self::Msg>, msg: Panic, sender: Sender) {
+        self.children[0].tell(msg, sender);
+    }
+}
+
+impl Receive<CountProbe> for StrategySup {
+    type Msg = StrategySupMsg;
+
+    fn receive(&mut self, _ctx: &Context<Self::Msg>, msg: CountProbe, sender: Sender) {
+        for child in &self.children {
+            child.tell(msg.clone(), sender.clone());
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn supervision_resume_failed_actor() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let sup = sys
+        .actor_of_args::<StrategySup, _>(
+            "supervisor",
+            SupervisorStrategy::one_for_one(Strategy::Resume),
+        )
+        .unwrap();
+
+    sup.tell(Count, None);
+    sup.tell(Panic, None);
+    sup.tell(Count, None);
+
+    // the failed child kept its state
+    let (probe, listen) = probe::<u32>();
+    sup.tell(CountProbe(probe), None);
+    p_assert_eq!(listen, 2);
+    p_assert_eq!(listen, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn supervision_restart_all_for_one() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let sup = sys
+        .actor_of_args::<StrategySup, _>(
+            "supervisor",
+            SupervisorStrategy::all_for_one(Strategy::Restart),
+        )
+        .unwrap();
+
+    sup.tell(Count, None);
+    sup.tell(Panic, None);
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    sup.tell(Count, None);
+
+    // both children were restarted and lost their state
+    let (probe, listen) = probe::<u32>();
+    sup.tell(CountProbe(probe), None);
+    p_assert_eq!(listen, 1);
+    p_assert_eq!(listen, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn supervision_stop_failed_actor() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let sup = sys
+        .actor_of_args::<StrategySup, _>(
+            "supervisor",
+            SupervisorStrategy::one_for_one(Strategy::Stop),
+        )
+        .unwrap();
+
+    sup.tell(Panic, None);
+    std::thread::sleep(std::time::Duration::from_millis(200));
+
+    let children: Vec<String> = sup.children().map(|c| c.name().to_string()).collect();
+    assert_eq!(children, vec!["child_b".to_string()]);
+}
+
+// Test Escalate strategy
+#[derive(Default)]
+struct EscalateSup {
+    sup: Option<ActorRef<StrategySupMsg>>,
+}
+
+impl Actor for EscalateSup {
+    type Msg = StrategySupMsg;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        self.sup = ctx
+            .actor_of_args::<StrategySup, _>(
+                "escalating",
+                SupervisorStrategy::one_for_one(Strategy::Escalate),
+            )
+            .ok();
+    }
+
+    fn recv(&mut self, _ctx: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
+        self.sup.as_ref().unwrap().send_msg(msg, sender);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn supervision_escalate_failure() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let sup = sys.actor_of::<EscalateSup>("supervisor").unwrap();
+
+    sup.tell(Count, None);
+    sup.tell(Panic, None);
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    sup.tell(Count, None);
+
+    // the escalating supervisor was restarted together with its children
+    let (probe, listen) = probe::<u32>();
+    sup.tell(CountProbe(probe), None);
+    p_assert_eq!(listen, 1);
+    p_assert_eq!(listen, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn supervision_restart_limit() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let sup = sys
+        .actor_of_args::<StrategySup, _>(
+            "supervisor",
+            SupervisorStrategy::one_for_one(Strategy::Restart).with_restart_limit(
+                3,
+                std::time::Duration::from_secs(60),
+                Strategy::Stop,
+            ),
+        )
+        .unwrap();
+
+    // three restarts are allowed
+    for _ in 0..3 {
+        sup.tell(Panic, None);
+    }
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    assert_eq!(sup.children().count(), 2);
+
+    // the fourth failure stops the child
+    sup.tell(Panic, None);
+    std::thread::sleep(std::time::Duration::from_millis(200));
+
+    let children: Vec<String> = sup.children().map(|c| c.name().to_string()).collect();
+    assert_eq!(children, vec!["child_b".to_string()]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn supervision_restart_backoff() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let sup = sys
+        .actor_of_args::<StrategySup, _>(
+            "supervisor",
+            SupervisorStrategy::one_for_one(Strategy::Restart).with_backoff(
+                std::time::Duration::from_millis(300),
+                std::time::Duration::from_secs(10),
+                0.0,
+            ),
+        )
+        .unwrap();
+
+    let (started, listen) = probe::<u32>();
+    sup.tell(CountProbe(started), None);
+    listen.recv();
+    listen.recv();
+
+    let start = std::time::Instant::now();
+    sup.tell(Panic, None);
+
+    // the failed child only answers once it has been restarted
+    let (probe, listen) = probe::<u32>();
+    sup.tell(CountProbe(probe), None);
+    p_assert_eq!(listen, 0);
+    p_assert_eq!(listen, 0);
+    assert!(start.elapsed() >= std::time::Duration::from_millis(300));
+}
@@ -1,6 +1,14 @@
+#[macro_use]
+extern crate riker_testkit;
+
 use tezedge_actor_system::actors::*;
 use tokio::runtime::Handle;
 
+use riker_testkit::probe::channel::{probe, ChannelProbe};
+use riker_testkit::probe::{Probe, ProbeReceive};
+
+use std::time::Duration;
+
 #[tokio::test]
 async fn system_create() {
     let backend = || Handle::current().into();
@@ -51,7 +59,9 @@ async fn system_shutdown() {
         .actor_of_args::<ShutdownTest, _>("test-actor-1", 1)
         .unwrap();
 
-    sys.shutdown().await;
+    let report = sys.shutdown().await;
+    assert!(report.is_complete());
+    assert!(!sys.user_root().has_children());
 }
 
 #[tokio::test]
@@ -70,3 +80,122 @@ async fn system_builder() {
         .unwrap();
     sys.shutdown().await;
 }
+
+/// Records "stopped" in `post_stop`, after blocking for `delay`
+struct Stopping {
+    probe: ChannelProbe<(), String>,
+    delay: Duration,
+}
+
+impl ActorFactoryArgs<(ChannelProbe<(), String>, Duration)> for Stopping {
+    fn create_args((probe, delay): (ChannelProbe<(), String>, Duration)) -> Self {
+        Stopping { probe, delay }
+    }
+}
+
+impl Actor for Stopping {
+    type Msg = ();
+
+    fn post_stop(&mut self) {
+        tokio::task::block_in_place(|| std::thread::sleep(self.delay));
+        self.probe.event("stopped".to_string());
+    }
+
+    fn recv(&mut self, _: &Context<Self::Msg>, _: Self::Msg, _: Sender) {}
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn system_shutdown_phases() {
+    let backend = Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    sys.actor_of_args::<Stopping, _>("stopping", (probe.clone(), Duration::ZERO))
+        .unwrap();
+
+    let shutdown = sys.coordinated_shutdown();
+    for (phase, name) in [
+        (ShutdownPhase::StopSystemActors, "system"),
+        (ShutdownPhase::Drain, "drain"),
+        (ShutdownPhase::StopAcceptingWork, "unbind"),
+    ] {
+        let probe = probe.clone();
+        shutdown.add_task(phase, name, move || async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            probe.event(name.to_string());
+        });
+    }
+
+    let report = sys.shutdown().await;
+    assert!(report.is_complete());
+
+    p_assert_eq!(listen, "unbind".to_string());
+    p_assert_eq!(listen, "drain".to_string());
+    p_assert_eq!(listen, "stopped".to_string());
+    p_assert_eq!(listen, "system".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn system_shutdown_timeout() {
+    let backend = Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    sys.actor_of_args::<Stopping, _>("stuck", (probe, Duration::from_secs(1)))
+        .unwrap();
+
+    let shutdown = sys.coordinated_shutdown();
+    shutdown.add_task(ShutdownPhase::Drain, "forever", std::future::pending);
+    shutdown.add_task(ShutdownPhase::Drain, "done", || async {});
+    shutdown.set_timeout(ShutdownPhase::Drain, Duration::from_millis(100));
+    shutdown.set_timeout(ShutdownPhase::StopUserActors, Duration::from_millis(200));
+
+    let report = sys.shutdown().await;
+    assert!(!report.is_complete());
+    assert_eq!(
+        report.timed_out_tasks,
+        [(ShutdownPhase::Drain, "forever".to_string())]
+    );
+    assert_eq!(
+        report.unstopped_actors,
+        ["/user/stuck".parse::<ActorPath>().unwrap()]
+    );
+
+    // the actor still stops eventually
+    p_assert_eq!(listen, "stopped".to_string());
+}
+
+#[cfg(target_os = "linux")]
+fn timer_threads() -> usize {
+    std::fs::read_dir("/proc/self/task")
+        .unwrap()
+        .filter_map(|task| std::fs::read_to_string(task.ok()?.path().join("comm")).ok())
+        .filter(|name| name.trim() == "actor-timer")
+        .count()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn system_shutdown_releases_resources() {
+    let metrics = Handle::current().metrics();
+    let tasks = metrics.num_alive_tasks();
+
+    for _ in 0..100 {
+        let sys = ActorSystem::new(Handle::current().into()).unwrap();
+        sys.actor_of_args::<ShutdownTest, _>("test-actor-1", 1)
+            .unwrap();
+        assert!(sys.shutdown().await.is_complete());
+    }
+
+    // the kernels of the system actors and the root terminate after shutdown
+    for _ in 0..50 {
+        if metrics.num_alive_tasks() <= tasks {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(metrics.num_alive_tasks() <= tasks);
+
+    // allows for systems of the other tests running meanwhile
+    #[cfg(target_os = "linux")]
+    assert!(timer_threads() < 10);
+}
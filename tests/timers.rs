@@ -0,0 +1,235 @@
+#[macro_use]
+extern crate riker_testkit;
+
+use tezedge_actor_system::actors::*;
+
+use riker_testkit::probe::channel::{probe, ChannelProbe};
+use riker_testkit::probe::{Probe, ProbeReceive};
+
+use std::time::Duration;
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// Starts timers on "start" and records what it receives
+struct Timers {
+    probe: ChannelProbe<(), String>,
+}
+
+impl ActorFactoryArgs<ChannelProbe<(), String>> for Timers {
+    fn create_args(probe: ChannelProbe<(), String>) -> Self {
+        Timers { probe }
+    }
+}
+
+impl Actor for Timers {
+    type Msg = String;
+
+    fn pre_start(&mut self, _ctx: &Context<Self::Msg>) {
+        self.probe.event("started".to_string());
+    }
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, _sender: Sender) {
+        match msg.as_str() {
+            "start" => {
+                ctx.start_timer("tick", MINUTE, "tick-1".to_string());
+                ctx.start_timer("tick", MINUTE * 2, "tick-2".to_string());
+                let beat = RepeatSchedule::fixed_rate(MINUTE, MINUTE);
+                ctx.start_repeat_timer("beat", beat, "beat".to_string());
+                self.probe.event(keys(ctx));
+            }
+            "list" => self.probe.event(keys(ctx)),
+            "cancel" => {
+                let cancelled = ctx.cancel_timer("beat") && !ctx.cancel_timer("beat");
+                self.probe.event(format!("cancelled {}", cancelled));
+            }
+            "panic" => panic!("// TEST PANIC // TEST PANIC // TEST PANIC //"),
+            _ => self.probe.event(msg),
+        }
+    }
+}
+
+fn keys(ctx: &Context<String>) -> String {
+    let keys: Vec<String> = ctx.timers().into_iter().filter_map(|t| t.key).collect();
+    keys.join(",")
+}
+
+fn system(clock: &VirtualClock) -> ActorSystem {
+    SystemBuilder::new()
+        .exec(tokio::runtime::Handle::current().into())
+        .timer(clock.timer())
+        .create()
+        .unwrap()
+}
+
+async fn wait_for(mut done: impl FnMut() -> bool) {
+    for _ in 0..500 {
+        if done() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("timed out");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn timers_keyed() {
+    let clock = VirtualClock::new();
+    let sys = system(&clock);
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Timers, _>("timers", probe).unwrap();
+    p_assert_eq!(listen, "started".to_string());
+
+    actor.tell("start".to_string(), None);
+    p_assert_eq!(listen, "tick,beat".to_string());
+    // the first "tick" timer was replaced
+    assert_eq!(clock.scheduled(), 2);
+
+    clock.advance(MINUTE * 2);
+    p_assert_eq!(listen, "beat".to_string());
+    p_assert_eq!(listen, "tick-2".to_string());
+    p_assert_eq!(listen, "beat".to_string());
+
+    actor.tell("list".to_string(), None);
+    p_assert_eq!(listen, "beat".to_string());
+
+    actor.tell("cancel".to_string(), None);
+    p_assert_eq!(listen, "cancelled true".to_string());
+    actor.tell("list".to_string(), None);
+    p_assert_eq!(listen, "".to_string());
+    assert_eq!(clock.scheduled(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn timers_cancelled_on_restart_and_stop() {
+    let clock = VirtualClock::new();
+    let sys = system(&clock);
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Timers, _>("timers", probe).unwrap();
+    p_assert_eq!(listen, "started".to_string());
+
+    actor.tell("start".to_string(), None);
+    p_assert_eq!(listen, "tick,beat".to_string());
+
+    actor.tell("panic".to_string(), None);
+    p_assert_eq!(listen, "started".to_string());
+    assert_eq!(clock.scheduled(), 0);
+    actor.tell("list".to_string(), None);
+    p_assert_eq!(listen, "".to_string());
+
+    actor.tell("start".to_string(), None);
+    p_assert_eq!(listen, "tick,beat".to_string());
+
+    sys.stop(&actor);
+    wait_for(|| clock.scheduled() == 0).await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn timers_end_with_receiver() {
+    let clock = VirtualClock::new();
+    let sys = system(&clock);
+
+    let (probe, listen) = probe();
+    let actor = sys.actor_of_args::<Timers, _>("timers", probe).unwrap();
+    p_assert_eq!(listen, "started".to_string());
+
+    // a schedule of the system, which the stopped actor can't cancel
+    sys.schedule(MINUTE, MINUTE, actor.clone(), None, "beat".to_string())
+        .detach();
+    clock.advance(MINUTE);
+    p_assert_eq!(listen, "beat".to_string());
+
+    sys.stop(&actor);
+    wait_for(|| {
+        clock.advance(MINUTE);
+        clock.scheduled() == 0
+    })
+    .await;
+}
+
+/// Lets its timers fire while it is busy, before replacing or cancelling them
+struct Late {
+    probe: ChannelProbe<(), String>,
+    clock: VirtualClock,
+}
+
+impl ActorFactoryArgs<(ChannelProbe<(), String>, VirtualClock)> for Late {
+    fn create_args((probe, clock): (ChannelProbe<(), String>, VirtualClock)) -> Self {
+        Late { probe, clock }
+    }
+}
+
+impl Actor for Late {
+    type Msg = String;
+
+    fn pre_start(&mut self, _ctx: &Context<Self::Msg>) {
+        self.probe.event("started".to_string());
+    }
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, _sender: Sender) {
+        match msg.as_str() {
+            "start" => {
+                ctx.start_timer("replaced", MINUTE, "old".to_string());
+                ctx.start_timer("cancelled", MINUTE, "cancelled".to_string());
+                self.probe.event(keys(ctx));
+            }
+            "late" => {
+                // the messages of both timers are in the mailbox after this
+                self.clock.advance(MINUTE);
+                ctx.start_timer("replaced", MINUTE, "new".to_string());
+                ctx.cancel_timer("cancelled");
+                self.probe.event(keys(ctx));
+            }
+            "panic" => {
+                self.clock.advance(MINUTE);
+                panic!("// TEST PANIC // TEST PANIC // TEST PANIC //");
+            }
+            _ => self.probe.event(msg),
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn timers_replaced_after_deadline() {
+    let clock = VirtualClock::new();
+    let sys = system(&clock);
+
+    let (probe, listen) = probe();
+    let actor = sys
+        .actor_of_args::<Late, _>("late", (probe, clock.clone()))
+        .unwrap();
+    p_assert_eq!(listen, "started".to_string());
+
+    actor.tell("start".to_string(), None);
+    p_assert_eq!(listen, "replaced,cancelled".to_string());
+    actor.tell("late".to_string(), None);
+    p_assert_eq!(listen, "replaced".to_string());
+
+    // the messages the old timers sent before they were replaced or cancelled are dropped
+    actor.tell("received".to_string(), None);
+    p_assert_eq!(listen, "received".to_string());
+    clock.advance(MINUTE);
+    p_assert_eq!(listen, "new".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn timers_dropped_on_restart_after_deadline() {
+    let clock = VirtualClock::new();
+    let sys = system(&clock);
+
+    let (probe, listen) = probe();
+    let actor = sys
+        .actor_of_args::<Late, _>("late", (probe, clock.clone()))
+        .unwrap();
+    p_assert_eq!(listen, "started".to_string());
+
+    actor.tell("start".to_string(), None);
+    p_assert_eq!(listen, "replaced,cancelled".to_string());
+    actor.tell("panic".to_string(), None);
+    p_assert_eq!(listen, "started".to_string());
+
+    // the restarted actor does not receive the messages of the cancelled timers
+    actor.tell("received".to_string(), None);
+    p_assert_eq!(listen, "received".to_string());
+}
@@ -0,0 +1,230 @@
+#[macro_use]
+extern crate riker_testkit;
+
+use tezedge_actor_system::actors::*;
+
+use riker_testkit::probe::channel::{probe, ChannelProbe};
+use riker_testkit::probe::{Probe, ProbeReceive};
+
+use std::time::Duration;
+
+type WatchProbe = ChannelProbe<(), String>;
+
+#[derive(Default)]
+struct DumbActor;
+
+impl Actor for DumbActor {
+    type Msg = ();
+
+    fn recv(&mut self, _: &Context<Self::Msg>, _: Self::Msg, _: Sender) {}
+}
+
+#[derive(Clone, Debug)]
+pub struct Ping;
+
+#[derive(Clone, Debug)]
+pub struct Unwatch;
+
+#[derive(Clone, Debug)]
+pub struct Gone;
+
+#[actor(Ping, Unwatch, Gone)]
+struct Watcher {
+    target: BasicActorRef,
+    probe: WatchProbe,
+    with_msg: bool,
+}
+
+impl ActorFactoryArgs<(BasicActorRef, WatchProbe, bool)> for Watcher {
+    fn create_args((target, probe, with_msg): (BasicActorRef, WatchProbe, bool)) -> Self {
+        Watcher {
+            target,
+            probe,
+            with_msg,
+        }
+    }
+}
+
+impl Actor for Watcher {
+    type Msg = WatcherMsg;
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        if self.with_msg {
+            ctx.watch_with(&self.target, Gone);
+        } else {
+            // watching twice still notifies only once
+            ctx.watch(&self.target);
+            ctx.watch(&self.target);
+        }
+    }
+
+    fn sys_recv(&mut self, _: &Context<Self::Msg>, msg: SystemMsg, _: Sender) {
+        if let SystemMsg::Terminated(terminated) = msg {
+            self.probe.event(terminated.path().to_string());
+        }
+    }
+
+    fn recv(&mut self, ctx: &Context<Self::Msg>, msg: Self::Msg, sender: Sender) {
+        self.receive(ctx, msg, sender);
+    }
+}
+
+impl Receive<Ping> for Watcher {
+    type Msg = WatcherMsg;
+
+    fn receive(&mut self, _: &Context<Self::Msg>, _: Ping, _: Sender) {
+        self.probe.event("ping".to_string());
+    }
+}
+
+impl Receive<Unwatch> for Watcher {
+    type Msg = WatcherMsg;
+
+    fn receive(&mut self, ctx: &Context<Self::Msg>, _: Unwatch, _: Sender) {
+        ctx.unwatch(&self.target);
+        self.probe.event("unwatched".to_string());
+    }
+}
+
+impl Receive<Gone> for Watcher {
+    type Msg = WatcherMsg;
+
+    fn receive(&mut self, _: &Context<Self::Msg>, _: Gone, sender: Sender) {
+        self.probe.event(format!("gone {}", sender.unwrap().path()));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn watch_terminated() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let target: BasicActorRef = sys.actor_of::<DumbActor>("target").unwrap().into();
+
+    let (probe, listen) = probe();
+    let watcher = sys
+        .actor_of_args::<Watcher, _>("watcher", (target.clone(), probe, false))
+        .unwrap();
+
+    watcher.tell(Ping, None);
+    p_assert_eq!(listen, "ping".to_string());
+
+    sys.stop(&target);
+    p_assert_eq!(listen, "/user/target".to_string());
+
+    // the notification is delivered only once
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    watcher.tell(Ping, None);
+    p_assert_eq!(listen, "ping".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn watch_already_terminated() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let target: BasicActorRef = sys.actor_of::<DumbActor>("target").unwrap().into();
+    sys.stop(&target);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let (probe, listen) = probe();
+    sys.actor_of_args::<Watcher, _>("watcher", (target, probe, false))
+        .unwrap();
+
+    p_assert_eq!(listen, "/user/target".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn watch_unwatch() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let target: BasicActorRef = sys.actor_of::<DumbActor>("target").unwrap().into();
+
+    let (probe, listen) = probe();
+    let watcher = sys
+        .actor_of_args::<Watcher, _>("watcher", (target.clone(), probe, false))
+        .unwrap();
+
+    watcher.tell(Unwatch, None);
+    p_assert_eq!(listen, "unwatched".to_string());
+
+    sys.stop(&target);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    watcher.tell(Ping, None);
+    p_assert_eq!(listen, "ping".to_string());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn watch_with_msg() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let target: BasicActorRef = sys.actor_of::<DumbActor>("target").unwrap().into();
+
+    let (probe, listen) = probe();
+    let watcher = sys
+        .actor_of_args::<Watcher, _>("watcher", (target.clone(), probe, true))
+        .unwrap();
+
+    watcher.tell(Ping, None);
+    p_assert_eq!(listen, "ping".to_string());
+
+    sys.stop(&target);
+    p_assert_eq!(listen, "gone /user/target".to_string());
+}
+
+/// Watches `count` children and stops them, reporting how many actors it
+/// still watches once all have terminated
+struct WatchMany {
+    count: usize,
+    terminated: usize,
+    probe: WatchProbe,
+}
+
+impl ActorFactoryArgs<(usize, WatchProbe)> for WatchMany {
+    fn create_args((count, probe): (usize, WatchProbe)) -> Self {
+        WatchMany {
+            count,
+            terminated: 0,
+            probe,
+        }
+    }
+}
+
+impl Actor for WatchMany {
+    type Msg = ();
+
+    fn pre_start(&mut self, ctx: &Context<Self::Msg>) {
+        for i in 0..self.count {
+            let child = ctx.actor_of::<DumbActor>(&format!("child-{}", i)).unwrap();
+            ctx.watch(&child);
+            ctx.stop(&child);
+        }
+    }
+
+    fn sys_recv(&mut self, ctx: &Context<Self::Msg>, msg: SystemMsg, _: Sender) {
+        if let SystemMsg::Terminated(_) = msg {
+            self.terminated += 1;
+            if self.terminated == self.count {
+                self.probe
+                    .event(format!("watching {}", ctx.watching().len()));
+            }
+        }
+    }
+
+    fn recv(&mut self, _: &Context<Self::Msg>, _: Self::Msg, _: Sender) {}
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn watch_forgets_terminated() {
+    let backend = tokio::runtime::Handle::current().into();
+    let sys = ActorSystem::new(backend).unwrap();
+
+    let (probe, listen) = probe();
+    sys.actor_of_args::<WatchMany, _>("watcher", (100, probe))
+        .unwrap();
+
+    p_assert_eq!(listen, "watching 0".to_string());
+}